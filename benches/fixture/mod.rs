@@ -0,0 +1,65 @@
+// Each bench target compiles this module separately and uses only some of it.
+#![allow(dead_code)]
+
+/// Builds a JSON array of `events` GitHub-style event objects. The output is
+/// deterministic so runs are comparable.
+pub fn events(events: usize) -> String {
+    let mut source = String::from("[");
+    for i in 0..events {
+        if i > 0 {
+            source.push(',');
+        }
+        source.push_str(&event(i));
+    }
+    source.push(']');
+    source
+}
+
+fn event(i: usize) -> String {
+    format!(
+        concat!(
+            "{{\"id\":\"{id}\",\"type\":\"PushEvent\",\"public\":{public},",
+            "\"actor\":{{\"id\":{actor},\"login\":\"user{actor}\",\"score\":{score}}},",
+            "\"repo\":{{\"id\":{repo},\"name\":\"user{actor}/repo{repo}\",",
+            "\"url\":\"https://api.github.com/repos/user{actor}/repo{repo}\"}},",
+            "\"payload\":{{\"size\":{size},\"distinct\":null,",
+            "\"message\":\"fix \\\"bug\\\" #{id}\\n\\tsee \\u00e9\",",
+            "\"shas\":[{size},{repo},{actor}]}}}}"
+        ),
+        id = 2489651045usize + i,
+        public = i.is_multiple_of(2),
+        actor = 665991 + i % 97,
+        score = (i as f64) * 0.25,
+        repo = 28688495 + i % 13,
+        size = i % 7,
+    )
+}
+
+/// Builds an array of `objects` objects with `keys` distinct keys each, so
+/// that hashing keys dominates the parse.
+pub fn wide_objects(objects: usize, keys: usize) -> String {
+    let mut source = String::from("[");
+    for i in 0..objects {
+        if i > 0 {
+            source.push(',');
+        }
+        source.push('{');
+        for k in 0..keys {
+            if k > 0 {
+                source.push(',');
+            }
+            source.push_str(&format!("\"field_{}_{}\":{}", k, i % 3, k));
+        }
+        source.push('}');
+    }
+    source.push(']');
+    source
+}
+
+/// Builds a flat array of `count` numbers with a mix of integers and fractions.
+pub fn numbers(count: usize) -> String {
+    let items: Vec<String> = (0..count)
+        .map(|i| format!("{}", (i as f64) * 0.37 - 1000.0))
+        .collect();
+    format!("[{}]", items.join(","))
+}
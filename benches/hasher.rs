@@ -0,0 +1,30 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use json_parser::{
+    map::HasherKind,
+    parser::{parse_with_options, ParseOptions},
+};
+
+mod fixture;
+
+fn bench_hasher(c: &mut Criterion) {
+    let source = fixture::wide_objects(1_000, 64);
+
+    let mut group = c.benchmark_group("object_hasher");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    for (name, hasher) in [
+        ("siphash", HasherKind::SipHash),
+        ("fxhash", HasherKind::FxHash),
+    ] {
+        let options = ParseOptions {
+            hasher,
+            ..Default::default()
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| parse_with_options(black_box(&source), &options).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_hasher);
+criterion_main!(benches);
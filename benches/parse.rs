@@ -0,0 +1,50 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use json_parser::{
+    parser::{parse, parse_array_of, parse_f64_array},
+    ser::to_string,
+};
+
+mod fixture;
+
+const SIZES: [(&str, usize); 3] = [("small", 10), ("medium", 1_000), ("large", 50_000)];
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, events) in SIZES {
+        let source = fixture::events(events);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &source, |b, source| {
+            b.iter(|| parse(black_box(source)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_to_string(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_string");
+    for (name, events) in SIZES {
+        let source = fixture::events(events);
+        let json = parse(&source).unwrap();
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &json, |b, json| {
+            b.iter(|| to_string(black_box(json)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_f64_array(c: &mut Criterion) {
+    let mut group = c.benchmark_group("f64_array");
+    let source = fixture::numbers(100_000);
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("parse_f64_array", |b| {
+        b.iter(|| parse_f64_array(black_box(&source)).unwrap())
+    });
+    group.bench_function("parse_array_of", |b| {
+        b.iter(|| parse_array_of::<f64>(black_box(&source)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_to_string, bench_f64_array);
+criterion_main!(benches);
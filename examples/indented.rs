@@ -0,0 +1,29 @@
+use std::time::SystemTime;
+
+use json_parser::parser::parse;
+
+// Times parsing a large pretty-printed document, where most of the input is indentation.
+fn main() {
+    let mut data = String::from("[\n");
+    for i in 0..200_000 {
+        if i > 0 {
+            data.push_str(",\n");
+        }
+        data.push_str("        {\n");
+        data.push_str(&format!("                \"id\": {},\n", i));
+        data.push_str("                \"tags\": [\n                        \"a\",\n                        \"b\"\n                ]\n");
+        data.push_str("        }");
+    }
+    data.push_str("\n]\n");
+
+    let start = SystemTime::now();
+
+    match parse(&data) {
+        Ok(_) => println!("ok"),
+        Err(err) => eprintln!("{}", err),
+    }
+
+    let end = SystemTime::now();
+    let dur = end.duration_since(start).expect("fail");
+    println!("duration: {}", dur.as_millis());
+}
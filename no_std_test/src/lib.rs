@@ -0,0 +1,33 @@
+//! `json_parser` with `default-features = false`, used from a `no_std` crate.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use json_parser::{parser::parse, ser::to_string};
+
+/// Parses `source` and writes it back out compactly.
+pub fn round_trip(source: &str) -> Option<String> {
+    to_string(&parse(source).ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use json_parser::{map::HasherKind, parser::JsonNode};
+
+    #[test]
+    fn parse_without_std() {
+        let json = parse(r#"{"b": [1, 2.5, null], "a": {"c": "é"}}"#).unwrap();
+
+        // only `std` has seeded SipHash, so this shows the feature is off
+        let map = json.as_map().unwrap();
+        assert_eq!(map.hasher().kind(), HasherKind::FxHash);
+        assert_eq!(json["a"]["c"], JsonNode::from("é"));
+        assert_eq!(
+            round_trip(r#"{"b": [1, 2.5, null], "a": {}}"#).unwrap(),
+            r#"{"b":[1,2.5,null],"a":{}}"#
+        );
+        assert!(round_trip("[1,").is_none());
+    }
+}
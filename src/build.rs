@@ -0,0 +1,226 @@
+use crate::{map::Map, parser::JsonNode};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildError {
+    pub message: String,
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "build error: {}", self.message)
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+/// Builds an object fluently:
+///
+/// ```
+/// use json_parser::build::{ArrayBuilder, ObjectBuilder};
+///
+/// let json = ObjectBuilder::new()
+///     .field("name", "json_parser")
+///     .field("tags", ArrayBuilder::new().push("json").push("parser"))
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct ObjectBuilder {
+    map: Map,
+    duplicate: Option<String>,
+}
+
+impl ObjectBuilder {
+    pub fn new() -> ObjectBuilder {
+        ObjectBuilder::default()
+    }
+
+    /// Adds a field. Adding the same key twice is an error reported by `build`.
+    pub fn field(mut self, key: &str, value: impl Into<JsonNode>) -> ObjectBuilder {
+        if self.map.insert(key.to_string(), value.into()).is_some() && self.duplicate.is_none() {
+            self.duplicate = Some(key.to_string());
+        }
+        self
+    }
+
+    pub fn try_build(self) -> Result<JsonNode, BuildError> {
+        match self.duplicate {
+            Some(key) => Err(BuildError {
+                message: format!("duplicate key \"{}\"", key),
+            }),
+            None => Ok(JsonNode::Object(self.map)),
+        }
+    }
+
+    /// Panics if a key was added twice; use `try_build` to handle that instead.
+    pub fn build(self) -> JsonNode {
+        match self.try_build() {
+            Ok(json) => json,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+/// What `JsonNode::object_from_pairs` does with a key that appears twice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Fail with a `BuildError` naming the key.
+    #[default]
+    Reject,
+    /// Keep the value of the key's first pair.
+    KeepFirst,
+    /// Keep the value of the key's last pair, as parsing does.
+    KeepLast,
+}
+
+impl JsonNode {
+    /// Builds an object from `pairs`, resolving repeated keys per `duplicates`.
+    ///
+    /// Members keep the order of `pairs`, and serialize in that order unless
+    /// `sort_keys` is set. A repeated key stays where it first appeared.
+    pub fn object_from_pairs(
+        pairs: Vec<(String, JsonNode)>,
+        duplicates: DuplicateKeys,
+    ) -> Result<JsonNode, BuildError> {
+        let mut map = Map::default();
+        for (key, value) in pairs {
+            match (map.get_mut(&key), duplicates) {
+                (None, _) => {
+                    map.insert(key, value);
+                }
+                (Some(_), DuplicateKeys::Reject) => {
+                    return Err(BuildError {
+                        message: format!("duplicate key \"{}\"", key),
+                    })
+                }
+                (Some(_), DuplicateKeys::KeepFirst) => {}
+                (Some(slot), DuplicateKeys::KeepLast) => *slot = value,
+            }
+        }
+        Ok(JsonNode::Object(map))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ArrayBuilder {
+    vec: Vec<JsonNode>,
+}
+
+impl ArrayBuilder {
+    pub fn new() -> ArrayBuilder {
+        ArrayBuilder::default()
+    }
+
+    pub fn push(mut self, value: impl Into<JsonNode>) -> ArrayBuilder {
+        self.vec.push(value.into());
+        self
+    }
+
+    pub fn build(self) -> JsonNode {
+        JsonNode::Array(self.vec)
+    }
+}
+
+impl From<ObjectBuilder> for JsonNode {
+    fn from(builder: ObjectBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl From<ArrayBuilder> for JsonNode {
+    fn from(builder: ArrayBuilder) -> Self {
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::parse, ser::to_string};
+
+    #[test]
+    fn nested_document() {
+        let json = ObjectBuilder::new()
+            .field("id", 665991)
+            .field("login", "petroav")
+            .field("admin", false)
+            .field("gravatar", JsonNode::Null)
+            .field(
+                "repos",
+                ArrayBuilder::new()
+                    .push(
+                        ObjectBuilder::new()
+                            .field("name", "6.828")
+                            .field("stars", 1.5),
+                    )
+                    .push(ArrayBuilder::new()),
+            )
+            .build();
+
+        let expected = parse(
+            r#"{"id":665991,"login":"petroav","admin":false,"gravatar":null,
+                "repos":[{"name":"6.828","stars":1.5},[]]}"#,
+        )
+        .unwrap();
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn duplicate_key() {
+        let err = ObjectBuilder::new()
+            .field("a", 1)
+            .field("a", 2)
+            .try_build()
+            .unwrap_err();
+
+        assert_eq!(err.message, "duplicate key \"a\"");
+    }
+
+    #[test]
+    fn object_from_pairs() {
+        let pairs = || {
+            vec![
+                ("b".to_string(), JsonNode::from(1)),
+                ("a".to_string(), JsonNode::from("x")),
+                ("b".to_string(), JsonNode::from(2)),
+            ]
+        };
+
+        let err = JsonNode::object_from_pairs(pairs(), DuplicateKeys::Reject).unwrap_err();
+        assert_eq!(err.message, "duplicate key \"b\"");
+
+        let json = JsonNode::object_from_pairs(pairs(), DuplicateKeys::KeepFirst).unwrap();
+        assert_eq!(json, parse(r#"{"a": "x", "b": 1}"#).unwrap());
+
+        let json = JsonNode::object_from_pairs(pairs(), DuplicateKeys::KeepLast).unwrap();
+        assert_eq!(json, parse(r#"{"a": "x", "b": 2}"#).unwrap());
+    }
+
+    #[test]
+    fn object_from_pairs_keeps_order() {
+        let pairs = ["zeta", "alpha", "mid", "beta"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, key)| (key.to_string(), JsonNode::from(i as i64)))
+            .collect();
+
+        let json = JsonNode::object_from_pairs(pairs, DuplicateKeys::Reject).unwrap();
+        assert_eq!(
+            to_string(&json).unwrap(),
+            r#"{"zeta":0,"alpha":1,"mid":2,"beta":3}"#
+        );
+
+        let pairs = vec![
+            ("b".to_string(), JsonNode::from(1)),
+            ("a".to_string(), JsonNode::from(2)),
+            ("b".to_string(), JsonNode::from(3)),
+        ];
+        let json = JsonNode::object_from_pairs(pairs, DuplicateKeys::KeepLast).unwrap();
+        assert_eq!(to_string(&json).unwrap(), r#"{"b":3,"a":2}"#);
+    }
+}
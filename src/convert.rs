@@ -0,0 +1,340 @@
+use crate::{
+    error::ConvError,
+    map::Map,
+    number::{exact_integer, Number},
+    parser::JsonNode,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+fn mismatch(expected: &'static str, node: &JsonNode) -> ConvError {
+    ConvError {
+        expected,
+        found: node.type_name(),
+    }
+}
+
+impl TryFrom<&JsonNode> for f64 {
+    type Error = ConvError;
+
+    fn try_from(node: &JsonNode) -> Result<Self, Self::Error> {
+        node.as_f64().ok_or_else(|| mismatch("a number", node))
+    }
+}
+
+impl TryFrom<&JsonNode> for i64 {
+    type Error = ConvError;
+
+    fn try_from(node: &JsonNode) -> Result<Self, Self::Error> {
+        let n = match node {
+            JsonNode::RawNumber(s) => exact_integer(s).and_then(|n| i64::try_from(n).ok()),
+            JsonNode::Number(n) => n.as_i64(),
+            _ => None,
+        };
+        n.ok_or_else(|| mismatch("an integer", node))
+    }
+}
+
+impl TryFrom<&JsonNode> for u64 {
+    type Error = ConvError;
+
+    fn try_from(node: &JsonNode) -> Result<Self, Self::Error> {
+        let n = match node {
+            JsonNode::RawNumber(s) => exact_integer(s).and_then(|n| u64::try_from(n).ok()),
+            JsonNode::Number(n) => n.as_u64(),
+            _ => None,
+        };
+        n.ok_or_else(|| mismatch("an unsigned integer", node))
+    }
+}
+
+impl TryFrom<&JsonNode> for bool {
+    type Error = ConvError;
+
+    fn try_from(node: &JsonNode) -> Result<Self, Self::Error> {
+        node.as_bool()
+            .copied()
+            .ok_or_else(|| mismatch("a boolean", node))
+    }
+}
+
+impl TryFrom<&JsonNode> for String {
+    type Error = ConvError;
+
+    fn try_from(node: &JsonNode) -> Result<Self, Self::Error> {
+        node.as_string()
+            .cloned()
+            .ok_or_else(|| mismatch("a string", node))
+    }
+}
+
+// Infallible conversions out of an owned node: `None` when the node holds
+// another type. Plain `String` is the exception and coerces any node.
+
+impl From<JsonNode> for Option<f64> {
+    fn from(node: JsonNode) -> Self {
+        node.as_f64()
+    }
+}
+
+impl From<JsonNode> for Option<bool> {
+    fn from(node: JsonNode) -> Self {
+        node.as_bool().copied()
+    }
+}
+
+impl From<JsonNode> for Option<String> {
+    fn from(node: JsonNode) -> Self {
+        match node {
+            JsonNode::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl From<JsonNode> for Option<Vec<JsonNode>> {
+    fn from(node: JsonNode) -> Self {
+        match node {
+            JsonNode::Array(vec) => Some(vec),
+            _ => None,
+        }
+    }
+}
+
+impl From<JsonNode> for Option<Map> {
+    fn from(node: JsonNode) -> Self {
+        match node {
+            JsonNode::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+}
+
+/// Same as `coerce_to_string`, but a string node gives up its buffer
+/// instead of being cloned.
+impl From<JsonNode> for String {
+    fn from(node: JsonNode) -> Self {
+        match node {
+            JsonNode::String(s) => s,
+            other => other.coerce_to_string(),
+        }
+    }
+}
+
+impl JsonNode {
+    /// Converts every element of the array under `key`. `None` if the key is
+    /// missing; an error if it is not an array or an element does not convert.
+    pub fn get_array_of<T>(&self, key: &str) -> Option<Result<Vec<T>, ConvError>>
+    where
+        T: for<'n> TryFrom<&'n JsonNode, Error = ConvError>,
+    {
+        let node = self.get(key)?;
+        let items = match node.as_vec() {
+            Some(items) => items,
+            None => return Some(Err(mismatch("an array", node))),
+        };
+        Some(items.iter().map(T::try_from).collect())
+    }
+
+    /// The elements as `u64`s, if this is an array of non-negative integers.
+    pub fn as_u64_array(&self) -> Option<Vec<u64>> {
+        self.as_vec()?
+            .iter()
+            .map(|v| u64::try_from(v).ok())
+            .collect()
+    }
+
+    /// The elements as `f64`s, if this is an array of numbers.
+    pub fn as_f64_array(&self) -> Option<Vec<f64>> {
+        self.as_vec()?.iter().map(JsonNode::as_f64).collect()
+    }
+
+    /// The elements as string slices, if this is an array of strings.
+    pub fn as_str_array(&self) -> Option<Vec<&str>> {
+        self.as_vec()?.iter().map(JsonNode::as_str).collect()
+    }
+}
+
+impl From<Number> for JsonNode {
+    fn from(n: Number) -> Self {
+        JsonNode::Number(n)
+    }
+}
+
+impl From<f64> for JsonNode {
+    fn from(n: f64) -> Self {
+        JsonNode::Number(Number::new(n))
+    }
+}
+
+impl From<i32> for JsonNode {
+    fn from(n: i32) -> Self {
+        JsonNode::Number(Number::from(n as i64))
+    }
+}
+
+impl From<i64> for JsonNode {
+    fn from(n: i64) -> Self {
+        JsonNode::Number(Number::from(n))
+    }
+}
+
+impl From<u64> for JsonNode {
+    fn from(n: u64) -> Self {
+        JsonNode::Number(Number::from(n))
+    }
+}
+
+impl From<bool> for JsonNode {
+    fn from(b: bool) -> Self {
+        JsonNode::Bool(b)
+    }
+}
+
+impl From<&str> for JsonNode {
+    fn from(s: &str) -> Self {
+        JsonNode::String(s.to_string())
+    }
+}
+
+impl From<String> for JsonNode {
+    fn from(s: String) -> Self {
+        JsonNode::String(s)
+    }
+}
+
+impl From<Vec<JsonNode>> for JsonNode {
+    fn from(vec: Vec<JsonNode>) -> Self {
+        JsonNode::Array(vec)
+    }
+}
+
+impl FromIterator<JsonNode> for JsonNode {
+    fn from_iter<I: IntoIterator<Item = JsonNode>>(iter: I) -> Self {
+        JsonNode::Array(iter.into_iter().collect())
+    }
+}
+
+/// Collects into an object. A repeated key keeps the last value.
+impl FromIterator<(String, JsonNode)> for JsonNode {
+    fn from_iter<I: IntoIterator<Item = (String, JsonNode)>>(iter: I) -> Self {
+        JsonNode::Object(iter.into_iter().collect::<Map>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{parse, parse_with_options, JsonNode, ParseOptions};
+
+    #[test]
+    fn integers() {
+        let json = parse("[-3,4.5,18446744073709551615]").unwrap();
+        let arr = json.as_vec().unwrap();
+
+        assert_eq!(i64::try_from(&arr[0]), Ok(-3));
+        assert!(i64::try_from(&arr[1]).is_err());
+        assert!(u64::try_from(&arr[0]).is_err());
+        assert_eq!(f64::try_from(&arr[1]), Ok(4.5));
+        assert_eq!(u64::try_from(&arr[2]), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn raw_integers() {
+        let options = ParseOptions {
+            raw_numbers: true,
+            ..Default::default()
+        };
+        let json =
+            parse_with_options("[1.0, 1e2, -2.50e1, 1.5, 18446744073709551615]", &options).unwrap();
+
+        assert_eq!(i64::try_from(&json[0]), Ok(1));
+        assert_eq!(i64::try_from(&json[1]), Ok(100));
+        assert_eq!(i64::try_from(&json[2]), Ok(-25));
+        assert!(i64::try_from(&json[3]).is_err());
+        assert!(i64::try_from(&json[4]).is_err());
+        assert_eq!(u64::try_from(&json[4]), Ok(u64::MAX));
+        assert!(u64::try_from(&json[2]).is_err());
+    }
+
+    #[test]
+    fn owned_options() {
+        let json = parse(r#"[2.5, "a", true, [1], {}]"#).unwrap();
+        let arr = json.as_vec().unwrap();
+
+        assert_eq!(Option::<f64>::from(arr[0].clone()), Some(2.5));
+        assert_eq!(Option::<f64>::from(arr[1].clone()), None);
+        assert_eq!(
+            Option::<String>::from(arr[1].clone()),
+            Some("a".to_string())
+        );
+        assert_eq!(Option::<String>::from(arr[0].clone()), None);
+        assert_eq!(Option::<bool>::from(arr[2].clone()), Some(true));
+        assert_eq!(
+            Option::<Vec<JsonNode>>::from(arr[3].clone()).map(|v| v.len()),
+            Some(1)
+        );
+        assert!(Option::<crate::map::Map>::from(arr[4].clone()).is_some());
+        assert!(Option::<crate::map::Map>::from(arr[3].clone()).is_none());
+    }
+
+    #[test]
+    fn into_string_coerces() {
+        let s: String = parse(r#""a""#).unwrap().into();
+        assert_eq!(s, "a");
+        let s: String = parse("[1, true]").unwrap().into();
+        assert_eq!(s, "[1,true]");
+    }
+
+    #[test]
+    fn get_array_of() {
+        let json = parse(r#"{"tags":["a","b"],"mixed":["a",1],"name":"x","empty":[]}"#).unwrap();
+
+        let tags: Vec<String> = json.get_array_of("tags").unwrap().unwrap();
+        assert_eq!(tags, ["a", "b"]);
+        assert_eq!(json.get_array_of::<f64>("empty"), Some(Ok(vec![])));
+        assert_eq!(json.get_array_of::<String>("missing"), None);
+
+        let err = json.get_array_of::<String>("mixed").unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "expected a string, found number");
+        let err = json.get_array_of::<String>("name").unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "expected an array, found string");
+    }
+
+    #[test]
+    fn typed_arrays() {
+        let json =
+            parse(r#"{"ids":[1,2,3],"xs":[1.5,-2],"tags":["a","b"],"mixed":[1,"a"]}"#).unwrap();
+
+        assert_eq!(json["ids"].as_u64_array(), Some(vec![1, 2, 3]));
+        assert_eq!(json["xs"].as_f64_array(), Some(vec![1.5, -2.0]));
+        assert_eq!(json["tags"].as_str_array(), Some(vec!["a", "b"]));
+        assert_eq!(json["xs"].as_u64_array(), None);
+
+        assert_eq!(json["mixed"].as_u64_array(), None);
+        assert_eq!(json["mixed"].as_f64_array(), None);
+        assert_eq!(json["mixed"].as_str_array(), None);
+        assert_eq!(json["missing"].as_u64_array(), None);
+        assert_eq!(parse("[]").unwrap().as_str_array(), Some(vec![]));
+    }
+
+    #[test]
+    fn collect_array() {
+        let json: JsonNode = (1..=3).map(|n| JsonNode::from(n * 2)).collect();
+        assert_eq!(json, parse("[2, 4, 6]").unwrap());
+
+        let empty: JsonNode = std::iter::empty::<JsonNode>().collect();
+        assert_eq!(empty, JsonNode::array());
+    }
+
+    #[test]
+    fn collect_object() {
+        let json: JsonNode = ["a", "b", "a"]
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (key.to_string(), JsonNode::from(i as i64)))
+            .collect();
+        assert_eq!(json, parse(r#"{"a": 2, "b": 1}"#).unwrap());
+    }
+}
@@ -0,0 +1,49 @@
+use crate::parser::JsonNode;
+use core::str::FromStr;
+use rust_decimal::Decimal;
+
+impl JsonNode {
+    /// Returns the number as an exact `Decimal`.
+    ///
+    /// Only a `RawNumber` keeps the literal's exact digits, so parse with
+    /// `ParseOptions::raw_numbers` when rounding matters: a parsed `Number`
+    /// has already been rounded to the nearest `f64`, unless it was an
+    /// integer small enough to be kept exactly.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            JsonNode::RawNumber(s) if s.contains(['e', 'E']) => Decimal::from_scientific(s).ok(),
+            JsonNode::RawNumber(s) => Decimal::from_str(s).ok(),
+            JsonNode::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(i), _) => Some(Decimal::from(i)),
+                (_, Some(u)) => Some(Decimal::from(u)),
+                _ => Decimal::try_from(n.as_f64()).ok(),
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, parse_with_options, ParseOptions};
+
+    #[test]
+    fn exact_decimal() {
+        let options = ParseOptions {
+            raw_numbers: true,
+            ..Default::default()
+        };
+        let json = parse_with_options("[0.1, 0.2, 1.5e2, -7]", &options).unwrap();
+
+        // 0.1 + 0.2 is exactly 0.3 in decimal, unlike in f64
+        let sum = json[0].as_decimal().unwrap() + json[1].as_decimal().unwrap();
+        assert_eq!(json[0].as_decimal(), Some(Decimal::new(1, 1)));
+        assert_eq!(sum, Decimal::new(3, 1));
+        assert_ne!(json[0].as_f64().unwrap() + json[1].as_f64().unwrap(), 0.3);
+        assert_eq!(json[2].as_decimal(), Some(Decimal::new(150, 0)));
+        assert_eq!(json[3].as_decimal(), Some(Decimal::new(-7, 0)));
+
+        assert_eq!(parse("\"0.1\"").unwrap().as_decimal(), None);
+    }
+}
@@ -0,0 +1,180 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::fmt::Display;
+
+/// What kind of failure a `ParseError` reports. More kinds may be added, so
+/// matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// Malformed input; `message` says what was wrong.
+    #[default]
+    Syntax,
+    /// The input is empty or holds only whitespace.
+    EmptyDocument,
+    /// The input ends in the middle of a value.
+    UnexpectedEof,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub message: String,
+    /// Byte offset of the offending token, if the error has one.
+    pub index: Option<usize>,
+    pub line: Option<usize>,
+    /// 1-based column of the offending token, counted in characters.
+    pub column: Option<usize>,
+}
+
+impl ParseError {
+    pub fn new(message: &str) -> ParseError {
+        ParseError {
+            kind: ParseErrorKind::Syntax,
+            message: message.to_string(),
+            index: None,
+            line: None,
+            column: None,
+        }
+    }
+
+    pub fn at(message: &str, source: &str, index: usize, line: usize) -> ParseError {
+        ParseError {
+            kind: ParseErrorKind::Syntax,
+            message: message.to_string(),
+            index: Some(index),
+            line: Some(line),
+            column: Some(column_at(source, index)),
+        }
+    }
+
+    /// An `UnexpectedEof` error, which has no position.
+    pub fn eof(message: &str) -> ParseError {
+        ParseError {
+            kind: ParseErrorKind::UnexpectedEof,
+            ..ParseError::new(message)
+        }
+    }
+
+    /// An `EmptyDocument` error, for input with nothing but whitespace.
+    pub fn empty() -> ParseError {
+        ParseError {
+            kind: ParseErrorKind::EmptyDocument,
+            ..ParseError::new("empty document")
+        }
+    }
+
+    /// Formats the error the way rustc does: the message, the offending line
+    /// of `source`, and a caret under the column. Errors without a position
+    /// (such as an unexpected end of input) point just past the last character.
+    pub fn render(&self, source: &str) -> String {
+        let index = self.index.unwrap_or(source.len()).min(source.len());
+        let line_start = source[..index].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[index..]
+            .find('\n')
+            .map_or(source.len(), |i| index + i);
+        let line = source[..line_start].matches('\n').count() + 1;
+        let column = column_at(source, index);
+
+        let text = source[line_start..line_end].trim_end_matches('\r');
+        let number = line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        format!(
+            "error: {}\n{} --> line {}, column {}\n{} |\n{} | {}\n{} | {}^\n",
+            self.message,
+            gutter,
+            line,
+            column,
+            gutter,
+            number,
+            text,
+            gutter,
+            " ".repeat(column - 1)
+        )
+    }
+}
+
+fn column_at(source: &str, index: usize) -> usize {
+    let line_start = source[..index].rfind('\n').map_or(0, |i| i + 1);
+    source[line_start..index].chars().count() + 1
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match (self.index, self.line) {
+            (Some(index), Some(line)) => write!(
+                f,
+                "error: {}, at index: {}, line: {}",
+                self.message, index, line
+            ),
+            _ => write!(f, "error: {}", self.message),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// Returned when a `JsonNode` cannot be converted into the requested Rust type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvError {
+    pub expected: &'static str,
+    pub found: &'static str,
+}
+
+impl Display for ConvError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "expected {}, found {}", self.expected, self.found)
+    }
+}
+
+impl core::error::Error for ConvError {}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseErrorKind;
+    use crate::parser::parse;
+
+    #[test]
+    fn render_points_at_column() {
+        let source = "{\n  \"a\": 1,\n  \"b\" 2\n}";
+        let err = parse(source).unwrap_err();
+
+        assert_eq!(err.line, Some(3));
+        assert_eq!(err.column, Some(7));
+        assert_eq!(
+            err.render(source),
+            "error: expect :\n  --> line 3, column 7\n  |\n3 |   \"b\" 2\n  |       ^\n"
+        );
+    }
+
+    #[test]
+    fn empty_document_is_not_eof() {
+        for source in ["", "   ", "\n\t "] {
+            let err = parse(source).unwrap_err();
+            assert_eq!(err.kind, ParseErrorKind::EmptyDocument);
+            assert_eq!(err.message, "empty document");
+        }
+
+        for source in ["[1,", "{\"a\":", "  [  "] {
+            assert_eq!(
+                parse(source).unwrap_err().kind,
+                ParseErrorKind::UnexpectedEof
+            );
+        }
+        assert_eq!(parse("[1,]").unwrap_err().kind, ParseErrorKind::Syntax);
+    }
+
+    #[test]
+    fn render_eof() {
+        let source = "[1,\n2,";
+        let err = parse(source).unwrap_err();
+
+        assert_eq!(
+            err.render(source),
+            "error: eof\n  --> line 2, column 3\n  |\n2 | 2,\n  |   ^\n"
+        );
+    }
+}
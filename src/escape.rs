@@ -0,0 +1,221 @@
+use crate::{error::ParseError, parser::SurrogatePolicy, ser::SerializeOptions};
+use alloc::string::String;
+use core::fmt::Write;
+
+/// Quotes `s` and escapes it so it can be embedded in a JSON document.
+pub fn escape_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escape_into(s, &mut escaped, &SerializeOptions::default());
+    escaped
+}
+
+/// Appends the quoted, escaped form of `s` to `out` without allocating
+/// anything else. With `options.ascii_only`, every non-ASCII character is
+/// written as a `\u` escape, using a UTF-16 surrogate pair for characters
+/// outside the Basic Multilingual Plane. With `options.escape_js_separators`,
+/// U+2028 and U+2029 are escaped as well.
+pub fn escape_into(s: &str, out: &mut String, options: &SerializeOptions) {
+    out.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            // writing to a `String` cannot fail
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            '\u{2028}' | '\u{2029}' if options.escape_js_separators => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c if options.ascii_only && !c.is_ascii() => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    let _ = write!(out, "\\u{:04x}", unit);
+                }
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+/// The length of `escape_string(s)`, without building it.
+pub(crate) fn escaped_len(s: &str) -> usize {
+    let body: usize = s
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' | '\n' | '\r' | '\t' | '\u{8}' | '\u{c}' => 2,
+            c if (c as u32) < 0x20 => 6,
+            c => c.len_utf8(),
+        })
+        .sum();
+    body + 2
+}
+
+/// Decodes the escape sequences in the body of a JSON string, i.e. the text
+/// between the quotes.
+pub fn unescape_string(s: &str) -> Result<String, ParseError> {
+    unescape(s).map_err(ParseError::new)
+}
+
+pub(crate) fn unescape(body: &str) -> Result<String, &'static str> {
+    unescape_quoted(body, '"', SurrogatePolicy::Error)
+}
+
+/// Like `unescape`, but for a string delimited by `quote`, which may then
+/// also be escaped, and with `surrogates` deciding what to do about a
+/// `\u` escape of an unpaired UTF-16 surrogate.
+pub(crate) fn unescape_quoted(
+    body: &str,
+    quote: char,
+    surrogates: SurrogatePolicy,
+) -> Result<String, &'static str> {
+    let mut chars = body.chars();
+    let mut escaped = String::with_capacity(body.len());
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            escaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some(c) => match c {
+                '\"' => escaped.push('\"'),
+                c if c == quote => escaped.push(c),
+                '\\' => escaped.push('\\'),
+                '/' => escaped.push('/'),
+                'n' => escaped.push('\n'),
+                'b' => escaped.push('\u{8}'),
+                'f' => escaped.push('\u{c}'),
+                'r' => escaped.push('\r'),
+                't' => escaped.push('\t'),
+                'u' => {
+                    let x = read_hex4(&mut chars)?;
+
+                    // characters outside the BMP arrive as a UTF-16 surrogate pair
+                    let c = if (0xD800..0xDC00).contains(&x) {
+                        // look ahead, so an unpaired high half leaves what follows alone
+                        let mut ahead = chars.clone();
+                        let low = match (ahead.next(), ahead.next()) {
+                            (Some('\\'), Some('u')) => read_hex4(&mut ahead)
+                                .ok()
+                                .filter(|low| (0xDC00..0xE000).contains(low)),
+                            _ => None,
+                        };
+                        low.and_then(|low| {
+                            chars = ahead;
+                            char::from_u32(0x10000 + ((x - 0xD800) << 10) + (low - 0xDC00))
+                        })
+                    } else {
+                        char::from_u32(x)
+                    };
+
+                    match (c, surrogates) {
+                        (Some(c), _) => escaped.push(c),
+                        (None, SurrogatePolicy::Error) => return Err("lone surrogate"),
+                        (None, SurrogatePolicy::ReplacementChar) => escaped.push('\u{fffd}'),
+                        (None, SurrogatePolicy::Passthrough) => {
+                            let _ = write!(escaped, "\\u{:04x}", x);
+                        }
+                    }
+                }
+                _ => return Err("invalid escape"),
+            },
+            None => return Err("invalid token"),
+        };
+    }
+
+    Ok(escaped)
+}
+
+fn read_hex4(chars: &mut core::str::Chars) -> Result<u32, &'static str> {
+    let mut x = 0;
+    for _ in 0..4 {
+        let digit = match chars.next() {
+            Some(c) => c.to_digit(16),
+            None => return Err("unexpected eof"),
+        };
+        match digit {
+            Some(d) => x = x * 16 + d,
+            None => return Err("parse \\u error"),
+        }
+    }
+    Ok(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_into_appends() {
+        let mut out = String::from("key=");
+        let text = "tab\there \"quoted\" \u{1f} é";
+        escape_into(text, &mut out, &SerializeOptions::default());
+        assert_eq!(out, format!("key={}", escape_string(text)));
+    }
+
+    #[test]
+    fn round_trip() {
+        let original = "say \"hi\"\nto 😀 and \\ \t\u{1}";
+        let escaped = escape_string(original);
+
+        assert_eq!(escaped, "\"say \\\"hi\\\"\\nto 😀 and \\\\ \\t\\u0001\"");
+        assert_eq!(
+            unescape_string(&escaped[1..escaped.len() - 1]).unwrap(),
+            original
+        );
+    }
+
+    #[test]
+    fn surrogate_pair() {
+        assert_eq!(unescape_string("\\ud83d\\ude00!").unwrap(), "😀!");
+        assert_eq!(unescape_string("\\uD83D\\uDE00").unwrap(), "😀");
+
+        let err = unescape_string("\\ud83d!").unwrap_err();
+        assert_eq!(err.message, "lone surrogate");
+        let err = unescape_string("\\ude00").unwrap_err();
+        assert_eq!(err.message, "lone surrogate");
+    }
+
+    #[test]
+    fn ascii_only() {
+        let options = SerializeOptions {
+            ascii_only: true,
+            ..Default::default()
+        };
+        let mut out = String::new();
+        escape_into("é😀\n", &mut out, &options);
+        assert_eq!(out, "\"\\u00e9\\ud83d\\ude00\\n\"");
+    }
+
+    #[test]
+    fn js_separators() {
+        let text = "a\u{2028}b\u{2029}";
+        let mut out = String::new();
+        escape_into(text, &mut out, &SerializeOptions::default());
+        assert_eq!(out, "\"a\u{2028}b\u{2029}\"");
+
+        let options = SerializeOptions {
+            escape_js_separators: true,
+            ..Default::default()
+        };
+        out.clear();
+        escape_into(text, &mut out, &options);
+        assert_eq!(out, "\"a\\u2028b\\u2029\"");
+    }
+
+    #[test]
+    fn invalid_escape() {
+        let err = unescape_string("abc\\q").unwrap_err();
+        assert_eq!(err.message, "invalid escape");
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    error::ParseError,
+    token::{Token, TokenType},
+    tokenizer::Tokenizer,
+};
+use alloc::string::String;
+
+/// Removes the insignificant whitespace from JSON text without building a
+/// tree. Tokens, including strings and number spellings, are copied verbatim.
+///
+/// Only lexical errors are reported; the nesting of brackets is not checked.
+pub fn minify(source: &str) -> Result<String, ParseError> {
+    let mut out = String::with_capacity(source.len());
+    let mut tokens = Tokens {
+        source,
+        tokenizer: Tokenizer::new(source),
+    };
+
+    while let Some((_, text)) = tokens.next()? {
+        out.push_str(text);
+    }
+
+    Ok(out)
+}
+
+/// Reformats JSON text with one member or element per line, indented by
+/// `indent` spaces per level, in a single pass over the tokens. Key order and
+/// number spellings stay as they are in `source`; empty containers stay `{}`
+/// and `[]`.
+///
+/// Like `minify`, only lexical errors are reported.
+pub fn prettify(source: &str, indent: usize) -> Result<String, ParseError> {
+    let mut out = String::with_capacity(source.len() * 2);
+    let mut tokens = Tokens {
+        source,
+        tokenizer: Tokenizer::new(source),
+    };
+    let mut depth = 0usize;
+    let mut next = tokens.next()?;
+
+    while let Some((token, text)) = next.take() {
+        next = tokens.next()?;
+
+        match token.token_type {
+            TokenType::LeftCurlyBracket | TokenType::LeftSquareBracket => {
+                out.push_str(text);
+                let closes = match &next {
+                    Some((close, _)) => matches!(
+                        (&token.token_type, &close.token_type),
+                        (TokenType::LeftCurlyBracket, TokenType::RightCurlyBracket)
+                            | (TokenType::LeftSquareBracket, TokenType::RightSquareBracket)
+                    ),
+                    None => false,
+                };
+                if closes {
+                    out.push_str(next.take().unwrap().1);
+                    next = tokens.next()?;
+                } else {
+                    depth += 1;
+                    newline(&mut out, indent, depth);
+                }
+            }
+            TokenType::RightCurlyBracket | TokenType::RightSquareBracket => {
+                depth = depth.saturating_sub(1);
+                newline(&mut out, indent, depth);
+                out.push_str(text);
+            }
+            TokenType::Comma => {
+                out.push(',');
+                newline(&mut out, indent, depth);
+            }
+            TokenType::Colon => out.push_str(": "),
+            _ => out.push_str(text),
+        }
+    }
+
+    Ok(out)
+}
+
+fn newline(out: &mut String, indent: usize, depth: usize) {
+    out.push('\n');
+    out.push_str(&" ".repeat(indent * depth));
+}
+
+/// Tokens paired with their source text, with lexical errors turned into `ParseError`s.
+struct Tokens<'a> {
+    source: &'a str,
+    tokenizer: Tokenizer<'a>,
+}
+
+impl<'a> Tokens<'a> {
+    fn next(&mut self) -> Result<Option<(Token<'a>, &'a str)>, ParseError> {
+        let token = match self.tokenizer.next() {
+            Some(token) => token,
+            None => return Ok(None),
+        };
+        if let TokenType::Error { kind, .. } = token.token_type {
+            return Err(ParseError::at(
+                kind.message(),
+                self.source,
+                token.index,
+                token.line,
+            ));
+        }
+
+        let text = &self.source[token.index..self.tokenizer.position()];
+        Ok(Some((token, text)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::GITHUB_EVENT, parser::parse, ser::to_string};
+
+    #[test]
+    fn minify_indented() {
+        let indented = "{\n  \"list\": [\n    1,\n    true,\n    { \"text\": \"keep  these \\\"spaces\\\"\" }\n  ]\n}\n";
+
+        let minified = minify(indented).unwrap();
+        assert_eq!(
+            minified,
+            r#"{"list":[1,true,{"text":"keep  these \"spaces\""}]}"#
+        );
+        assert_eq!(minified, to_string(&parse(indented).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn minify_keeps_number_text() {
+        assert_eq!(minify("[ 1.50 , -0 , 1E2 ]").unwrap(), "[1.50,-0,1E2]");
+        assert_eq!(
+            parse(&minify(GITHUB_EVENT).unwrap()).unwrap(),
+            parse(GITHUB_EVENT).unwrap()
+        );
+    }
+
+    #[test]
+    fn prettify_compact() {
+        let compact = r#"{"b":[1.50,{"x":[],"y":{}}],"a":"s, t: [u]"}"#;
+
+        let pretty = prettify(compact, 2).unwrap();
+        assert_eq!(
+            pretty,
+            "{\n  \"b\": [\n    1.50,\n    {\n      \"x\": [],\n      \"y\": {}\n    }\n  ],\n  \"a\": \"s, t: [u]\"\n}"
+        );
+        assert_eq!(minify(&pretty).unwrap(), compact);
+        assert_eq!(prettify(&pretty, 2).unwrap(), pretty);
+    }
+
+    #[test]
+    fn minify_error() {
+        let err = minify("[1, nul]").unwrap_err();
+        assert_eq!(err.message, "unknown keyword");
+        assert_eq!(err.index, Some(4));
+    }
+}
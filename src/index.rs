@@ -0,0 +1,176 @@
+use crate::{parser::JsonNode, pointer::array_index};
+use alloc::string::ToString;
+use core::ops::{Index, IndexMut};
+
+pub(crate) static NULL: JsonNode = JsonNode::Null;
+
+impl JsonNode {
+    /// Looks up `key` if this node is an object.
+    pub fn get(&self, key: &str) -> Option<&JsonNode> {
+        match self {
+            JsonNode::Object(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonNode> {
+        match self {
+            JsonNode::Object(map) => map.get_mut(key),
+            _ => None,
+        }
+    }
+
+    /// Looks up element `i` if this node is an array.
+    pub fn get_index(&self, i: usize) -> Option<&JsonNode> {
+        match self {
+            JsonNode::Array(vec) => vec.get(i),
+            _ => None,
+        }
+    }
+
+    pub fn get_index_mut(&mut self, i: usize) -> Option<&mut JsonNode> {
+        match self {
+            JsonNode::Array(vec) => vec.get_mut(i),
+            _ => None,
+        }
+    }
+
+    /// Follows `path` down the tree: each segment is an object key, or an
+    /// element index when the node at that point is an array.
+    pub fn get_path(&self, path: &[&str]) -> Option<&JsonNode> {
+        let mut node = self;
+        for segment in path {
+            node = match node {
+                JsonNode::Array(vec) => vec.get(array_index(segment)?)?,
+                _ => node.get(segment)?,
+            };
+        }
+        Some(node)
+    }
+
+    /// The string at `path`, or `None` if it is missing or not a string.
+    pub fn get_str_path(&self, path: &[&str]) -> Option<&str> {
+        self.get_path(path)?.as_str()
+    }
+
+    /// The number at `path` as an `f64`, raw numbers included.
+    pub fn get_f64_path(&self, path: &[&str]) -> Option<f64> {
+        self.get_path(path)?.as_f64()
+    }
+
+    pub fn get_bool_path(&self, path: &[&str]) -> Option<bool> {
+        self.get_path(path)?.as_bool().copied()
+    }
+
+    /// The boolean under `key`, or `None` if it is missing or not a boolean.
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key)?.as_bool().copied()
+    }
+
+    /// The number under `key` as an `f64`, raw numbers included.
+    pub fn get_number(&self, key: &str) -> Option<f64> {
+        self.get(key)?.as_f64()
+    }
+
+    /// The string under `key`, or `None` if it is missing or not a string.
+    pub fn get_string(&self, key: &str) -> Option<&str> {
+        self.get(key)?.as_str()
+    }
+}
+
+/// Missing keys and non-objects index to `Null`.
+impl Index<&str> for JsonNode {
+    type Output = JsonNode;
+
+    fn index(&self, key: &str) -> &JsonNode {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// Indexing a missing key inserts `Null` under it, so `node["k"] = value` adds
+/// new keys. Panics if this node is not an object.
+impl IndexMut<&str> for JsonNode {
+    fn index_mut(&mut self, key: &str) -> &mut JsonNode {
+        match self {
+            JsonNode::Object(map) => map.entry(key.to_string()).or_insert(JsonNode::Null),
+            _ => panic!("cannot index a non-object node with a key"),
+        }
+    }
+}
+
+/// Out-of-bounds indices and non-arrays index to `Null`.
+impl Index<usize> for JsonNode {
+    type Output = JsonNode;
+
+    fn index(&self, i: usize) -> &JsonNode {
+        self.get_index(i).unwrap_or(&NULL)
+    }
+}
+
+/// Panics if this node is not an array or `i` is out of bounds.
+impl IndexMut<usize> for JsonNode {
+    fn index_mut(&mut self, i: usize) -> &mut JsonNode {
+        match self {
+            JsonNode::Array(vec) => &mut vec[i],
+            _ => panic!("cannot index a non-array node with a number"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::GITHUB_EVENT, parser::parse};
+
+    #[test]
+    fn index() {
+        let json = parse(r#"{"a":[1,{"b":true}]}"#).unwrap();
+
+        assert_eq!(json["a"][1]["b"].as_bool(), Some(&true));
+        assert!(json["missing"][3]["b"].is_null());
+    }
+
+    #[test]
+    fn index_mut() {
+        let mut json = parse(r#"{"a":[1,{"b":true}]}"#).unwrap();
+
+        json["a"][1]["b"] = JsonNode::from(2_f64);
+        json["a"][1]["c"] = JsonNode::Null;
+        *json.get_mut("a").unwrap().get_index_mut(0).unwrap() = JsonNode::Bool(false);
+
+        assert_eq!(json, parse(r#"{"a":[false,{"b":2,"c":null}]}"#).unwrap());
+    }
+
+    #[test]
+    fn typed_paths() {
+        let json = parse(GITHUB_EVENT).unwrap();
+
+        assert_eq!(json.get_str_path(&["repo", "name"]), Some("petroav/6.828"));
+        assert_eq!(json.get_f64_path(&["actor", "id"]), Some(665991.0));
+        assert_eq!(json.get_bool_path(&["public"]), Some(true));
+
+        assert_eq!(json.get_str_path(&["actor", "id"]), None);
+        assert_eq!(json.get_f64_path(&["actor", "missing"]), None);
+        assert_eq!(json.get_path(&[]), Some(&json));
+
+        let json = parse(r#"{"a":[{"b":"x"}]}"#).unwrap();
+        assert_eq!(json.get_str_path(&["a", "0", "b"]), Some("x"));
+        assert_eq!(json.get_str_path(&["a", "1", "b"]), None);
+    }
+
+    #[test]
+    fn typed_fields() {
+        let json = parse(GITHUB_EVENT).unwrap();
+
+        assert_eq!(json.get_string("type"), Some("CreateEvent"));
+        assert_eq!(json.get_bool("public"), Some(true));
+        assert_eq!(json["actor"].get_number("id"), Some(665991.0));
+
+        // "id" is a string at the top level
+        assert_eq!(json.get_number("id"), None);
+        assert_eq!(json.get_string("id"), Some("2489651045"));
+        assert_eq!(json.get_bool("type"), None);
+        assert_eq!(json.get_string("missing"), None);
+        assert_eq!(json["actor"]["id"].get_string("id"), None);
+    }
+}
@@ -1,3 +1,40 @@
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+extern crate alloc;
+
+pub mod build;
+mod convert;
+#[cfg(feature = "decimal")]
+mod decimal;
+pub mod error;
+mod escape;
+#[cfg(test)]
+mod fixtures;
+mod format;
+mod index;
+pub mod map;
+pub mod merge;
+pub mod number;
 pub mod parser;
+pub mod patch;
+mod pointer;
+pub mod query;
+#[cfg(feature = "std")]
+pub mod rc;
+#[cfg(feature = "std")]
+mod reader;
+pub mod ser;
+mod shape;
+mod sorted;
+pub mod stream;
 mod token;
 mod tokenizer;
+pub mod trivia;
+pub mod yaml;
+
+pub use escape::{escape_into, escape_string, unescape_string};
+pub use format::{minify, prettify};
+#[cfg(feature = "std")]
+pub use reader::{parse_reader, ArrayStream};
+pub use token::{LexErrorKind, Token, TokenType};
+pub use tokenizer::{Tokenizer, TokenizerOptions};
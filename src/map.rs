@@ -0,0 +1,207 @@
+use crate::parser::JsonNode;
+use alloc::{string::String, vec::Vec};
+use core::hash::{BuildHasher, Hasher};
+use indexmap::IndexMap;
+#[cfg(feature = "std")]
+use std::collections::hash_map::{DefaultHasher, RandomState};
+
+/// The map behind `JsonNode::Object`, the same type with or without `std`.
+///
+/// Members keep the order they were inserted in, and `shift_remove` keeps the
+/// order of the rest. Equality ignores order, as it does for JSON objects.
+pub type Map = IndexMap<String, JsonNode, ObjectHasher>;
+
+/// An empty object map that hashes with `kind`.
+pub(crate) fn new_map(kind: HasherKind) -> Map {
+    Map::with_hasher(ObjectHasher::new(kind))
+}
+
+/// Empties `map` and returns its entries, keeping its hasher so the map can
+/// be refilled in place.
+pub(crate) fn take_entries(map: &mut Map) -> Vec<(String, JsonNode)> {
+    map.drain(..).collect()
+}
+
+/// Which hash function object maps use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HasherKind {
+    /// The standard library's randomly seeded SipHash, which resists
+    /// hash-flooding from untrusted input. Without `std` there is no source
+    /// of random seeds, so this falls back to `FxHash`.
+    #[default]
+    SipHash,
+    /// FxHash, the much cheaper hash used inside rustc. It is not seeded, so
+    /// only use it for trusted input.
+    FxHash,
+}
+
+/// A `BuildHasher` that picks its hash function at runtime, so parse options
+/// can choose one without changing the type of `Map`.
+#[derive(Debug, Clone)]
+pub struct ObjectHasher(HasherRepr);
+
+#[derive(Debug, Clone)]
+enum HasherRepr {
+    #[cfg(feature = "std")]
+    SipHash(RandomState),
+    FxHash,
+}
+
+impl ObjectHasher {
+    pub fn new(kind: HasherKind) -> ObjectHasher {
+        match kind {
+            #[cfg(feature = "std")]
+            HasherKind::SipHash => ObjectHasher(HasherRepr::SipHash(RandomState::new())),
+            _ => ObjectHasher(HasherRepr::FxHash),
+        }
+    }
+
+    /// The hash function in use, which is `FxHash` for every map without
+    /// `std`.
+    pub fn kind(&self) -> HasherKind {
+        match self.0 {
+            #[cfg(feature = "std")]
+            HasherRepr::SipHash(_) => HasherKind::SipHash,
+            HasherRepr::FxHash => HasherKind::FxHash,
+        }
+    }
+}
+
+impl Default for ObjectHasher {
+    fn default() -> Self {
+        ObjectHasher::new(HasherKind::default())
+    }
+}
+
+impl BuildHasher for ObjectHasher {
+    type Hasher = ObjectHasherState;
+
+    fn build_hasher(&self) -> ObjectHasherState {
+        ObjectHasherState(match &self.0 {
+            #[cfg(feature = "std")]
+            HasherRepr::SipHash(state) => StateRepr::SipHash(state.build_hasher()),
+            HasherRepr::FxHash => StateRepr::FxHash(FxHasher::default()),
+        })
+    }
+}
+
+pub struct ObjectHasherState(StateRepr);
+
+enum StateRepr {
+    #[cfg(feature = "std")]
+    SipHash(DefaultHasher),
+    FxHash(FxHasher),
+}
+
+impl Hasher for ObjectHasherState {
+    fn finish(&self) -> u64 {
+        match &self.0 {
+            #[cfg(feature = "std")]
+            StateRepr::SipHash(h) => h.finish(),
+            StateRepr::FxHash(h) => h.finish(),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        match &mut self.0 {
+            #[cfg(feature = "std")]
+            StateRepr::SipHash(h) => h.write(bytes),
+            StateRepr::FxHash(h) => h.write(bytes),
+        }
+    }
+}
+
+const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+/// The word-at-a-time multiply-rotate hash from rustc.
+#[derive(Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(FX_SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            let mut word = [0; 8];
+            word.copy_from_slice(chunk);
+            self.add(u64::from_le_bytes(word));
+        }
+        for &byte in chunks.remainder() {
+            self.add(byte as u64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, parse_with_options, ParseOptions};
+
+    #[test]
+    fn fx_hash_objects() {
+        let src = r#"{"a":1,"b":{"c":[1,2],"d":"e"}}"#;
+        let options = ParseOptions {
+            hasher: HasherKind::FxHash,
+            ..Default::default()
+        };
+
+        let json = parse_with_options(src, &options).unwrap();
+        assert_eq!(json.as_map().unwrap().hasher().kind(), HasherKind::FxHash);
+        assert_eq!(json["b"]["d"].as_string().unwrap(), "e");
+        assert_eq!(json, parse(src).unwrap());
+    }
+
+    #[test]
+    fn insertion_order() {
+        let json = parse(r#"{"b":[1,{"d":null}],"a":"x","c":true,"a":1}"#).unwrap();
+        let keys: Vec<&String> = json.as_map().unwrap().keys().collect();
+
+        assert_eq!(keys, ["b", "a", "c"]);
+        assert_eq!(json["a"], parse("1").unwrap());
+        assert_eq!(json["b"][1]["d"], JsonNode::Null);
+        assert_eq!(
+            json,
+            parse(r#"{"c":true,"a":1,"b":[1,{"d":null}]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn removal_keeps_order() {
+        let mut map: Map = (0..10)
+            .map(|i| (i.to_string(), JsonNode::from(i)))
+            .collect();
+
+        assert_eq!(map.shift_remove("3"), Some(JsonNode::from(3)));
+        assert_eq!(map.insert("3".to_string(), JsonNode::Null), None);
+        assert_eq!(
+            map.insert("5".to_string(), JsonNode::Null),
+            Some(JsonNode::from(5))
+        );
+
+        let keys: Vec<&str> = map.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["0", "1", "2", "4", "5", "6", "7", "8", "9", "3"]);
+    }
+
+    #[test]
+    fn fx_hasher_distinguishes_keys() {
+        let hash = |s: &str| {
+            let mut h = FxHasher::default();
+            h.write(s.as_bytes());
+            h.finish()
+        };
+
+        assert_eq!(hash("gravatar_id"), hash("gravatar_id"));
+        assert_ne!(hash("gravatar_id"), hash("avatar_url"));
+    }
+}
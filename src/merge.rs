@@ -0,0 +1,255 @@
+use crate::{map::Map, parser::JsonNode};
+
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    pub array_strategy: ArrayStrategy,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArrayStrategy {
+    /// Replace the target array with the patch array, as RFC 7386 specifies.
+    #[default]
+    Replace,
+    /// Append the patch array's elements to the target array.
+    Concat,
+}
+
+/// Applies `patch` to `target` as an RFC 7386 JSON merge patch.
+pub fn merge(target: JsonNode, patch: JsonNode) -> JsonNode {
+    merge_with_options(target, patch, &MergeOptions::default())
+}
+
+pub fn merge_with_options(target: JsonNode, patch: JsonNode, options: &MergeOptions) -> JsonNode {
+    match (target, patch) {
+        (JsonNode::Object(mut target), JsonNode::Object(patch)) => {
+            merge_object(&mut target, patch, options);
+            JsonNode::Object(target)
+        }
+        (_, JsonNode::Object(patch)) => {
+            let mut target = Map::default();
+            merge_object(&mut target, patch, options);
+            JsonNode::Object(target)
+        }
+        (JsonNode::Array(mut target), JsonNode::Array(patch))
+            if options.array_strategy == ArrayStrategy::Concat =>
+        {
+            target.extend(patch);
+            JsonNode::Array(target)
+        }
+        (_, patch) => patch,
+    }
+}
+
+fn merge_object(target: &mut Map, patch: Map, options: &MergeOptions) {
+    for (key, value) in patch {
+        if value.is_null() {
+            target.shift_remove(&key);
+            continue;
+        }
+
+        // merge in place so the key keeps its position
+        match target.get_mut(&key) {
+            Some(old) => *old = merge_with_options(old.take(), value, options),
+            None => {
+                target.insert(key, merge_with_options(JsonNode::Null, value, options));
+            }
+        }
+    }
+}
+
+impl JsonNode {
+    /// Deep-merges `other` into this node. Objects are merged key by key at
+    /// every level and keys only in `other` are copied over. Wherever both
+    /// sides have a value and they are not both objects, `on_conflict`
+    /// receives this node's value and `other`'s and returns the result.
+    pub fn merge_with(
+        &mut self,
+        other: &JsonNode,
+        mut on_conflict: impl FnMut(&JsonNode, &JsonNode) -> JsonNode,
+    ) {
+        self.merge_with_callback(other, &mut on_conflict);
+    }
+
+    fn merge_with_callback(
+        &mut self,
+        other: &JsonNode,
+        on_conflict: &mut impl FnMut(&JsonNode, &JsonNode) -> JsonNode,
+    ) {
+        match (self, other) {
+            (JsonNode::Object(target), JsonNode::Object(other)) => {
+                for (key, value) in other {
+                    match target.get_mut(key) {
+                        Some(existing) => existing.merge_with_callback(value, on_conflict),
+                        None => {
+                            target.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+            (target, other) => *target = on_conflict(target, other),
+        }
+    }
+
+    /// Builds an RFC 7386 merge patch that turns `base` into `target`, so
+    /// `merge(base.clone(), JsonNode::diff(&base, &target)) == target`.
+    ///
+    /// Merge patches cannot set a member to `null`, so a `null` member in
+    /// `target` is removed instead.
+    pub fn diff(base: &JsonNode, target: &JsonNode) -> JsonNode {
+        let (base, target) = match (base, target) {
+            (JsonNode::Object(base), JsonNode::Object(target)) => (base, target),
+            _ => return target.clone(),
+        };
+
+        let mut patch = Map::default();
+        for key in base.keys() {
+            if !target.contains_key(key) {
+                patch.insert(key.clone(), JsonNode::Null);
+            }
+        }
+        for (key, value) in target {
+            match base.get(key) {
+                Some(old) if old == value => {}
+                Some(old) => {
+                    patch.insert(key.clone(), JsonNode::diff(old, value));
+                }
+                None => {
+                    patch.insert(key.clone(), JsonNode::diff(&JsonNode::Null, value));
+                }
+            }
+        }
+        JsonNode::Object(patch)
+    }
+
+    /// Merges two arrays of records: each element of `patch` is merged, as
+    /// an RFC 7386 merge patch, into the element of `base` with the same
+    /// value under `key`, or appended if there is none or it lacks `key`.
+    /// Does nothing unless both are arrays.
+    pub fn merge_arrays_by_key(base: &mut JsonNode, patch: &JsonNode, key: &str) {
+        let (base, patch) = match (base, patch) {
+            (JsonNode::Array(base), JsonNode::Array(patch)) => (base, patch),
+            _ => return,
+        };
+
+        for item in patch {
+            let existing = item
+                .get(key)
+                .and_then(|id| base.iter_mut().find(|record| record.get(key) == Some(id)));
+            match existing {
+                Some(record) => *record = merge(record.take(), item.clone()),
+                None => base.push(item.clone()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::parse, ser::to_string};
+
+    #[test]
+    fn merge_arrays_by_key() {
+        let mut base = parse(r#"[{"id":1,"v":"a","n":1},{"id":2,"v":"b"},{"v":"no id"}]"#).unwrap();
+        let patch =
+            parse(r#"[{"id":2,"v":"B","n":null},{"id":3,"v":"c"},{"id":1,"n":2},{"v":"x"}]"#)
+                .unwrap();
+
+        JsonNode::merge_arrays_by_key(&mut base, &patch, "id");
+        assert_eq!(
+            base,
+            parse(r#"[{"id":1,"v":"a","n":2},{"id":2,"v":"B"},{"v":"no id"},{"id":3,"v":"c"},{"v":"x"}]"#)
+                .unwrap()
+        );
+
+        let mut scalar = JsonNode::from(1);
+        JsonNode::merge_arrays_by_key(&mut scalar, &patch, "id");
+        assert_eq!(scalar, JsonNode::from(1));
+    }
+
+    #[test]
+    fn merge_patch() {
+        let target = parse(r#"{"a":"b","c":{"d":"e","f":"g"}}"#).unwrap();
+        let patch = parse(r#"{"a":"z","c":{"f":null}}"#).unwrap();
+
+        let expected = parse(r#"{"a":"z","c":{"d":"e"}}"#).unwrap();
+        assert_eq!(merge(target, patch), expected);
+    }
+
+    #[test]
+    fn merge_patch_keeps_key_order() {
+        let target = parse(r#"{"a":1,"b":2,"c":3}"#).unwrap();
+        let merged = merge(target, parse(r#"{"a":9,"d":4}"#).unwrap());
+        assert_eq!(to_string(&merged).unwrap(), r#"{"a":9,"b":2,"c":3,"d":4}"#);
+    }
+
+    #[test]
+    fn array_replace() {
+        let target = parse(r#"{"a":[1,2]}"#).unwrap();
+        let patch = parse(r#"{"a":[3]}"#).unwrap();
+
+        assert_eq!(merge(target, patch), parse(r#"{"a":[3]}"#).unwrap());
+    }
+
+    #[test]
+    fn array_concat() {
+        let target = parse(r#"{"a":[1,2]}"#).unwrap();
+        let patch = parse(r#"{"a":[3]}"#).unwrap();
+        let options = MergeOptions {
+            array_strategy: ArrayStrategy::Concat,
+        };
+
+        let merged = merge_with_options(target, patch, &options);
+        assert_eq!(merged, parse(r#"{"a":[1,2,3]}"#).unwrap());
+    }
+
+    #[test]
+    fn diff_round_trip() {
+        let base = parse(r#"{"a":"b","c":{"d":"e","f":"g"},"h":[1,2],"i":1}"#).unwrap();
+        let target = parse(r#"{"a":"z","c":{"d":"e","x":{"y":1}},"h":[1],"j":true}"#).unwrap();
+
+        let patch = JsonNode::diff(&base, &target);
+        assert_eq!(
+            patch,
+            parse(r#"{"a":"z","c":{"f":null,"x":{"y":1}},"h":[1],"i":null,"j":true}"#).unwrap()
+        );
+        assert_eq!(merge(base, patch), target);
+    }
+
+    #[test]
+    fn diff_unchanged() {
+        let doc = parse(r#"{"a":{"b":[1,2]}}"#).unwrap();
+        assert_eq!(JsonNode::diff(&doc, &doc), parse("{}").unwrap());
+    }
+
+    #[test]
+    fn merge_with_conflicts() {
+        let mut config = parse(r#"{"retries":2,"name":"a","limits":{"cpu":1,"mem":[1]}}"#).unwrap();
+        let other =
+            parse(r#"{"retries":3,"name":"b","limits":{"cpu":4,"disk":9,"mem":[2]}}"#).unwrap();
+
+        config.merge_with(&other, |a, b| match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => JsonNode::from(a + b),
+            _ => b.clone(),
+        });
+
+        assert_eq!(
+            config,
+            parse(r#"{"retries":5,"name":"b","limits":{"cpu":5,"disk":9,"mem":[2]}}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn merge_with_mismatched_types() {
+        let mut doc = parse(r#"{"a":{"x":1}}"#).unwrap();
+        let mut conflicts = vec![];
+
+        doc.merge_with(&parse(r#"{"a":"flat"}"#).unwrap(), |a, b| {
+            conflicts.push((a.type_name(), b.type_name()));
+            a.clone()
+        });
+
+        assert_eq!(conflicts, [("object", "string")]);
+        assert_eq!(doc, parse(r#"{"a":{"x":1}}"#).unwrap());
+    }
+}
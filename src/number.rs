@@ -0,0 +1,393 @@
+use alloc::string::String;
+use core::{
+    cmp::Ordering,
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
+
+/// A parsed JSON number. Unlike a bare `f64` it is `Eq`, `Ord` and `Hash`,
+/// so it can be used as a map key or stored in a set.
+///
+/// Integers that fit an `i64` or `u64` are kept exactly, so
+/// `9007199254740993` stays distinct from `9007199254740992` even though
+/// both round to the same `f64`. Everything else is held as an `f64`.
+///
+/// Equality and ordering are by value, so `1` and `1.0` are the same number
+/// and `-0` equals `0`. NaN equals itself and sorts after every other number,
+/// including infinity.
+#[derive(Debug, Clone, Copy)]
+pub struct Number(Repr);
+
+#[derive(Debug, Clone, Copy)]
+enum Repr {
+    PosInt(u64),
+    /// Always below zero.
+    NegInt(i64),
+    Float(f64),
+}
+
+impl Number {
+    pub fn new(n: f64) -> Number {
+        Number(Repr::Float(n))
+    }
+
+    pub fn as_f64(self) -> f64 {
+        match self.0 {
+            Repr::PosInt(n) => n as f64,
+            Repr::NegInt(n) => n as f64,
+            Repr::Float(n) => n,
+        }
+    }
+
+    /// The value as an `i64` when it is a whole number in range, e.g. for
+    /// `2` or `2.0` but not `2.5`.
+    pub fn as_i64(self) -> Option<i64> {
+        i64::try_from(self.as_i128()?).ok()
+    }
+
+    /// The value as a `u64` when it is a whole number in range.
+    pub fn as_u64(self) -> Option<u64> {
+        u64::try_from(self.as_i128()?).ok()
+    }
+
+    /// Whether the value has no fractional part, e.g. `2` or `2.0`.
+    pub fn is_integer(self) -> bool {
+        match self.0 {
+            Repr::Float(n) => is_integral(n),
+            _ => true,
+        }
+    }
+
+    /// The value when it is held as an integer rather than a float.
+    pub(crate) fn integer(self) -> Option<i128> {
+        match self.0 {
+            Repr::PosInt(n) => Some(n as i128),
+            Repr::NegInt(n) => Some(n as i128),
+            Repr::Float(_) => None,
+        }
+    }
+
+    /// The value when it is a whole number an `i64` or `u64` can hold. Every
+    /// such float converts exactly.
+    fn as_i128(self) -> Option<i128> {
+        match self.0 {
+            Repr::Float(n) if is_integral(n) && (I64_MIN..U64_END).contains(&n) => Some(n as i128),
+            _ => self.integer(),
+        }
+    }
+
+    /// The float with -0 folded into 0 and every NaN into one NaN, so values
+    /// that compare equal also have the same bits.
+    fn canonical(n: f64) -> f64 {
+        if n.is_nan() {
+            f64::NAN
+        } else {
+            n + 0.0
+        }
+    }
+}
+
+/// `i64::MIN` and `u64::MAX + 1` as floats, both exact.
+const I64_MIN: f64 = -9_223_372_036_854_775_808.0;
+const U64_END: f64 = 18_446_744_073_709_551_616.0;
+
+/// Compares an integer with a float without rounding either.
+fn cmp_int_float(i: i128, f: f64) -> Ordering {
+    if f.is_nan() || f >= U64_END {
+        return Ordering::Less;
+    }
+    if f < I64_MIN {
+        return Ordering::Greater;
+    }
+    // in this range truncating is exact, and so is going back to a float
+    let whole = f as i128;
+    i.cmp(&whole)
+        .then_with(|| (whole as f64).total_cmp(&(f + 0.0)))
+}
+
+impl Default for Number {
+    fn default() -> Number {
+        Number(Repr::PosInt(0))
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Number {}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Number) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Number) -> Ordering {
+        match (self.integer(), other.integer(), self.0, other.0) {
+            (Some(a), Some(b), ..) => a.cmp(&b),
+            (Some(a), None, _, Repr::Float(b)) => cmp_int_float(a, b),
+            (None, Some(b), Repr::Float(a), _) => cmp_int_float(b, a).reverse(),
+            _ => Number::canonical(self.as_f64()).total_cmp(&Number::canonical(other.as_f64())),
+        }
+    }
+}
+
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // whole numbers hash the same whichever way they are held
+        match (self.as_i128(), self.0) {
+            (Some(n), _) => n.hash(state),
+            (None, Repr::Float(n)) => Number::canonical(n).to_bits().hash(state),
+            (None, _) => unreachable!("integers always have an i128 value"),
+        }
+    }
+}
+
+impl PartialEq<f64> for Number {
+    fn eq(&self, other: &f64) -> bool {
+        *self == Number::new(*other)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(n: f64) -> Self {
+        Number::new(n)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(n: i64) -> Self {
+        if n < 0 {
+            Number(Repr::NegInt(n))
+        } else {
+            Number(Repr::PosInt(n as u64))
+        }
+    }
+}
+
+impl From<u64> for Number {
+    fn from(n: u64) -> Self {
+        Number(Repr::PosInt(n))
+    }
+}
+
+impl From<Number> for f64 {
+    fn from(n: Number) -> Self {
+        n.as_f64()
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            Repr::PosInt(n) => write!(f, "{}", n),
+            Repr::NegInt(n) => write!(f, "{}", n),
+            Repr::Float(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// The number JSON text such as `12` or `-1.5e3` stands for: integer
+/// literals that fit an `i64` or `u64` exactly, anything else as the nearest
+/// `f64`. `-0` stays a float so its sign survives.
+pub(crate) fn parse_number(text: &str) -> Option<Number> {
+    if !text.contains(['.', 'e', 'E']) {
+        if let Ok(n) = text.parse::<u64>() {
+            return Some(Number::from(n));
+        }
+        match text.parse::<i64>() {
+            Ok(n) if n < 0 => return Some(Number::from(n)),
+            _ => {}
+        }
+    }
+    text.parse::<f64>().ok().map(Number::new)
+}
+
+/// `n.fract() == 0.0` without needing `std`: every float of magnitude 2^52
+/// or more is a whole number, and smaller ones survive a trip through `i64`.
+pub(crate) fn is_integral(n: f64) -> bool {
+    n.is_finite() && (n.abs() >= 4_503_599_627_370_496.0 || n as i64 as f64 == n)
+}
+
+/// Rewrites the text of a JSON number in one spelling per value and
+/// precision: a lowercase `e` with no `+` or leading zeros in the exponent,
+/// no zero exponent and no sign on zero. With `trim_zeros` the fraction's
+/// trailing zeros go too, so `1.50E+02` becomes `1.5e2`.
+pub(crate) fn normalize_text(text: &str, trim_zeros: bool) -> String {
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(i) => (&text[..i], Some(&text[i + 1..])),
+        None => (text, None),
+    };
+
+    let mut out = String::from(mantissa);
+    if trim_zeros && out.contains('.') {
+        let len = out.trim_end_matches('0').trim_end_matches('.').len();
+        out.truncate(len);
+    }
+    if out.starts_with('-') && out[1..].bytes().all(|b| b == b'0' || b == b'.') {
+        out.remove(0);
+    }
+
+    if let Some(exponent) = exponent {
+        let (sign, digits) = match exponent.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", exponent.strip_prefix('+').unwrap_or(exponent)),
+        };
+        let digits = digits.trim_start_matches('0');
+        if !digits.is_empty() {
+            out.push('e');
+            out.push_str(sign);
+            out.push_str(digits);
+        }
+    }
+    out
+}
+
+/// The value of number text such as `1.0`, `-12` or `1.5e3` when it is
+/// exactly an integer an `i64` or `u64` can hold, worked out from the digits
+/// rather than through `f64` so nothing is rounded on the way.
+pub(crate) fn exact_integer(text: &str) -> Option<i128> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(i) => (&text[..i], text[i + 1..].parse::<i64>().ok()?),
+        None => (text, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    // the value is digits * 10^shift
+    let digits = [int, frac].concat();
+    let mut shift = exponent.checked_sub(frac.len() as i64)?;
+    let mut digits = digits.trim_start_matches('0');
+    while shift < 0 && digits.ends_with('0') {
+        digits = &digits[..digits.len() - 1];
+        shift += 1;
+    }
+    if digits.is_empty() {
+        return Some(0);
+    }
+    // `u64::MAX` has 20 digits, so anything longer is out of range
+    if shift < 0 || (digits.len() as i64).checked_add(shift)? > 20 {
+        return None;
+    }
+
+    let magnitude = digits.parse::<i128>().ok()? * 10i128.pow(shift as u32);
+    let n = if negative { -magnitude } else { magnitude };
+    (i64::MIN as i128..=u64::MAX as i128)
+        .contains(&n)
+        .then_some(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeSet, HashSet};
+
+    #[test]
+    fn exact_integers() {
+        assert_eq!(exact_integer("1.0"), Some(1));
+        assert_eq!(exact_integer("1e3"), Some(1000));
+        assert_eq!(exact_integer("-2.50e1"), Some(-25));
+        assert_eq!(exact_integer("-0.0"), Some(0));
+        assert_eq!(
+            exact_integer("-9223372036854775808"),
+            Some(i64::MIN as i128)
+        );
+        assert_eq!(exact_integer("-9223372036854775809"), None);
+        assert_eq!(
+            exact_integer("18446744073709551615"),
+            Some(u64::MAX as i128)
+        );
+        assert_eq!(exact_integer("18446744073709551616"), None);
+        assert_eq!(exact_integer("1.5"), None);
+        assert_eq!(exact_integer("1.0000000000000000001"), None);
+        assert_eq!(exact_integer("1e400"), None);
+        assert_eq!(exact_integer("1e9223372036854775807"), None);
+        assert_eq!(exact_integer("0e9223372036854775807"), Some(0));
+    }
+
+    #[test]
+    fn ordered_set() {
+        let set: BTreeSet<Number> = [2.0, 1.5, 1.0, 2.0].into_iter().map(Number::from).collect();
+
+        let values: Vec<f64> = set.into_iter().map(Number::as_f64).collect();
+        assert_eq!(values, vec![1.0, 1.5, 2.0]);
+    }
+
+    #[test]
+    fn nan_and_zero() {
+        assert_eq!(Number::new(f64::NAN), Number::new(-f64::NAN));
+        assert!(Number::new(f64::NAN) > Number::new(f64::INFINITY));
+        assert_eq!(Number::new(-0.0), Number::new(0.0));
+
+        let set: HashSet<Number> = [0.0, -0.0, f64::NAN, f64::NAN, 1.0]
+            .into_iter()
+            .map(Number::from)
+            .collect();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn large_integers() {
+        let a = parse_number("9007199254740993").unwrap();
+        let b = parse_number("9007199254740992").unwrap();
+        assert_ne!(a, b);
+        assert!(a > b);
+        assert_eq!(a.as_u64(), Some(9007199254740993));
+        assert_eq!(a.to_string(), "9007199254740993");
+
+        let max = parse_number("18446744073709551615").unwrap();
+        assert_eq!(max.as_u64(), Some(u64::MAX));
+        assert_eq!(max.as_i64(), None);
+        assert!(max < Number::new(18446744073709551616.0));
+        assert_eq!(
+            parse_number("-9223372036854775808").unwrap().as_i64(),
+            Some(i64::MIN)
+        );
+        // past u64 it falls back to a float
+        assert_eq!(
+            parse_number("18446744073709551616").unwrap(),
+            18446744073709551616.0
+        );
+    }
+
+    #[test]
+    fn integers_and_floats() {
+        assert_eq!(Number::from(2_i64), Number::new(2.0));
+        assert_eq!(Number::from(0_u64), Number::new(-0.0));
+        assert!(Number::from(-3_i64) < Number::new(-2.5));
+        assert!(Number::from(2_u64) > Number::new(1.5));
+        assert!(Number::from(u64::MAX) < Number::new(f64::NAN));
+        assert!(Number::from(i64::MIN) > Number::new(f64::NEG_INFINITY));
+        assert_eq!(Number::new(2.5).as_i64(), None);
+        assert_eq!(Number::new(-1.0).as_u64(), None);
+        assert_eq!(parse_number("-0").unwrap().to_string(), "-0");
+
+        let set: HashSet<Number> = [Number::from(1_u64), Number::new(1.0), Number::from(-1_i64)]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn normalize_text() {
+        let norm = |text| super::normalize_text(text, false);
+        assert_eq!(norm("1.50E+02"), "1.50e2");
+        assert_eq!(norm("1e-007"), "1e-7");
+        assert_eq!(norm("2.5e0"), "2.5");
+        assert_eq!(norm("-0.00"), "0.00");
+        assert_eq!(norm("-12"), "-12");
+
+        let trim = |text| super::normalize_text(text, true);
+        assert_eq!(trim("1.50E+02"), "1.5e2");
+        assert_eq!(trim("10.000"), "10");
+        assert_eq!(trim("-0.0"), "0");
+        assert_eq!(trim("100"), "100");
+    }
+}
@@ -1,207 +1,1040 @@
 use crate::{
-    token::{Token, TokenType},
-    tokenizer::Tokenizer,
+    error::{ConvError, ParseError},
+    escape::unescape_quoted,
+    map::{new_map, take_entries, HasherKind, Map},
+    number::{exact_integer, normalize_text, parse_number, Number},
+    pointer::escape_token,
+    token::{LexErrorKind, Token, TokenType},
+    tokenizer::{Tokenizer, TokenizerOptions},
+};
+use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
 };
-use std::collections::{HashMap, VecDeque};
 
-pub fn parse(source: &str) -> Result<JsonNode, String> {
+pub fn parse(source: &str) -> Result<JsonNode, ParseError> {
     JsonParser::new(source).parse()
 }
 
-struct JsonParser<'a> {
+pub fn parse_with_options(source: &str, options: &ParseOptions) -> Result<JsonNode, ParseError> {
+    JsonParser::with_options(source, options.clone()).parse()
+}
+
+/// Parses sloppy, hand-written JSON: comments, single-quoted strings,
+/// unquoted keys, trailing commas and missing commas between array elements
+/// are all accepted. See `ParseOptions::relaxed`.
+pub fn parse_relaxed(source: &str) -> Result<JsonNode, ParseError> {
+    JsonParser::with_options(source, ParseOptions::relaxed()).parse()
+}
+
+/// Parses a single value starting at byte `offset` of `source`, returning it
+/// together with the offset just past its end so the caller can resume there.
+pub fn parse_from(source: &str, offset: usize) -> Result<(JsonNode, usize), ParseError> {
+    let mut parser = JsonParser::new(source);
+    parser.tokenizer = Tokenizer::new_at(source, offset);
+
+    let json = parser.parse()?;
+    Ok((json, parser.position()))
+}
+
+/// Parses the value at the start of `source` and returns it with the byte
+/// offset just past its end, for JSON followed by other data. Whatever comes
+/// after the value is left for the caller; see `parse_from` to resume there.
+pub fn try_parse(source: &str) -> Result<(JsonNode, usize), ParseError> {
+    parse_from(source, 0)
+}
+
+/// Parses `source`, skipping malformed array elements and object members
+/// instead of stopping at the first error. Returns the tree built from the
+/// well-formed parts and every error in source order. A mismatched bracket
+/// or an early end of input still ends the parse, leaving no tree.
+pub fn parse_recoverable(source: &str) -> (Option<JsonNode>, Vec<ParseError>) {
+    let mut parser = JsonParser::new(source);
+    parser.errors = Some(vec![]);
+
+    let result = parser.parse();
+    let mut errors = parser.errors.take().unwrap_or_default();
+    match result {
+        Ok(json) => (Some(json), errors),
+        Err(e) => {
+            errors.push(e);
+            (None, errors)
+        }
+    }
+}
+
+/// Counters collected while parsing, for profiling large documents.
+#[cfg(feature = "trace")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseTrace {
+    /// Tokens read from the tokenizer.
+    pub tokens: usize,
+    /// String values and object keys run through unescaping.
+    pub strings_escaped: usize,
+    /// The deepest nesting of arrays and objects reached.
+    pub max_depth: usize,
+}
+
+/// The exact source text of every scalar in a document, keyed by the JSON
+/// Pointer of the value it became. See `parse_with_spans`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceSpans<'a> {
+    spans: BTreeMap<String, &'a str>,
+}
+
+impl<'a> SourceSpans<'a> {
+    /// The source text of the scalar at `ptr`, quotes included for strings.
+    pub fn get(&self, ptr: &str) -> Option<&'a str> {
+        self.spans.get(ptr).copied()
+    }
+
+    /// Every pointer and its source text, in pointer order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &'a str)> + '_ {
+        self.spans.iter().map(|(ptr, text)| (ptr.as_str(), *text))
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+}
+
+/// Parses `source` like `parse`, also recording where each string, number,
+/// boolean and null came from, so a validator can point at the exact text
+/// of a value it rejects. The spans are slices of `source`.
+pub fn parse_with_spans(source: &str) -> Result<(JsonNode, SourceSpans<'_>), ParseError> {
+    let mut parser = JsonParser::new(source);
+    parser.spans = Some((String::new(), SourceSpans::default()));
+    let json = parser.parse()?;
+    let (_, spans) = parser.spans.take().unwrap_or_default();
+    Ok((json, spans))
+}
+
+/// Parses `source` like `parse`, also returning counters for each phase.
+#[cfg(feature = "trace")]
+pub fn parse_traced(source: &str) -> Result<(JsonNode, ParseTrace), ParseError> {
+    let mut parser = JsonParser::new(source);
+    let json = parser.parse()?;
+    Ok((json, parser.trace))
+}
+
+/// Parses a JSON array and converts every element into `T`.
+pub fn parse_array_of<T>(source: &str) -> Result<Vec<T>, ParseError>
+where
+    T: for<'n> TryFrom<&'n JsonNode, Error = ConvError>,
+{
+    let json = parse(source)?;
+    let vec = match json.as_vec() {
+        Some(vec) => vec,
+        None => return Err(ParseError::new("expected an array")),
+    };
+
+    let mut items = Vec::with_capacity(vec.len());
+    for (i, item) in vec.iter().enumerate() {
+        match T::try_from(item) {
+            Ok(item) => items.push(item),
+            Err(e) => return Err(ParseError::new(&format!("element {}: {}", i, e))),
+        }
+    }
+    Ok(items)
+}
+
+/// Parses an array of numbers straight into `f64`s, reading tokens directly
+/// instead of building `JsonNode`s. Much faster than `parse_array_of::<f64>`
+/// for large numeric arrays.
+pub fn parse_f64_array(source: &str) -> Result<Vec<f64>, ParseError> {
+    let mut tokenizer = Tokenizer::new(source);
+    let error =
+        |message: &str, token: &Token| ParseError::at(message, source, token.index, token.line);
+
+    match tokenizer.next() {
+        Some(token) if token.token_type == TokenType::LeftSquareBracket => {}
+        Some(token) => return Err(error("expected an array", &token)),
+        None => return Err(ParseError::empty()),
+    }
+
+    let mut items = Vec::new();
+    loop {
+        let token = match tokenizer.next() {
+            Some(token) => token,
+            None => return Err(ParseError::eof("eof")),
+        };
+        match token.token_type {
+            TokenType::Number { text } => match text.parse::<f64>() {
+                Ok(n) => items.push(n),
+                Err(_) => return Err(error("invalid number", &token)),
+            },
+            TokenType::RightSquareBracket if items.is_empty() => return Ok(items),
+            TokenType::Error { kind, .. } => return Err(error(kind.message(), &token)),
+            _ => return Err(error("expected a number", &token)),
+        }
+
+        match tokenizer.next() {
+            Some(token) => match token.token_type {
+                TokenType::Comma => {}
+                TokenType::RightSquareBracket => return Ok(items),
+                _ => return Err(error("expected comma or end of array", &token)),
+            },
+            None => return Err(ParseError::eof("eof")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Keep numbers as their source text (`JsonNode::RawNumber`) instead of parsing them into `f64`.
+    pub raw_numbers: bool,
+    /// What to do with numbers outside the range of `f64`.
+    pub number_overflow: NumberOverflow,
+    /// Whether `NaN` and `Infinity` are accepted as numbers.
+    pub non_finite: NonFinite,
+    /// Which comments to skip like whitespace.
+    pub comments: CommentStyle,
+    /// Accept `'single quoted'` strings.
+    pub allow_single_quotes: bool,
+    /// Accept identifier object keys such as `{a: 1}`.
+    pub allow_unquoted_keys: bool,
+    /// Accept a comma after the last element of an array or object.
+    pub allow_trailing_commas: bool,
+    /// Accept array elements separated only by whitespace, e.g. `[1 2]`.
+    pub allow_missing_commas: bool,
+    /// What to do with a `\u` escape of an unpaired UTF-16 surrogate, which
+    /// no Rust string can hold.
+    pub lone_surrogates: SurrogatePolicy,
+    /// Reject strings and keys whose source text between the quotes is longer than this many bytes.
+    pub max_string_len: Option<usize>,
+    /// The hash function for object maps. Keep the default SipHash for untrusted input.
+    pub hasher: HasherKind,
+    /// Reject documents with arrays and objects nested more than this many levels deep.
+    pub max_depth: Option<usize>,
+    /// Accept a string, number, boolean or null as the whole document, as
+    /// RFC 8259 does. When false, the top level must be an object or array
+    /// as RFC 4627 required. On by default.
+    pub allow_top_level_scalar: bool,
+    /// Fail with "memory budget exceeded" once the parsed tree would take
+    /// roughly this many bytes: string and key bytes plus a fixed size per value.
+    pub max_total_bytes: Option<usize>,
+    /// Fail with "too many elements" once the document holds more than this
+    /// many array elements and object members in total.
+    pub max_elements: Option<usize>,
+    /// Let equal string values share one allocation, for repetitive data
+    /// such as logs. A `JsonNode` owns each of its strings, so only
+    /// `rc::parse_rc`, whose tree holds `Rc<str>`, acts on this.
+    pub intern_strings: bool,
+    /// Normalize object keys to this Unicode form, so keys that differ only in composition collide.
+    #[cfg(feature = "normalize-keys")]
+    pub normalize_keys: Option<NfKind>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            raw_numbers: false,
+            number_overflow: NumberOverflow::default(),
+            non_finite: NonFinite::default(),
+            comments: CommentStyle::default(),
+            allow_single_quotes: false,
+            allow_unquoted_keys: false,
+            allow_trailing_commas: false,
+            allow_missing_commas: false,
+            lone_surrogates: SurrogatePolicy::default(),
+            max_string_len: None,
+            hasher: HasherKind::default(),
+            max_depth: None,
+            allow_top_level_scalar: true,
+            max_total_bytes: None,
+            max_elements: None,
+            intern_strings: false,
+            #[cfg(feature = "normalize-keys")]
+            normalize_keys: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Every syntax extension turned on, for quick-and-dirty tooling.
+    pub fn relaxed() -> ParseOptions {
+        ParseOptions {
+            comments: CommentStyle::Both,
+            non_finite: NonFinite::Allow,
+            allow_single_quotes: true,
+            allow_unquoted_keys: true,
+            allow_trailing_commas: true,
+            allow_missing_commas: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Configures a parser fluently instead of filling in `ParseOptions`:
+///
+/// ```
+/// use json_parser::parser::{CommentStyle, ParserBuilder};
+///
+/// let parser = ParserBuilder::new()
+///     .comments(CommentStyle::SlashSlash)
+///     .trailing_commas(true);
+/// let json = parser.parse("[1, 2, // two\n]").unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParserBuilder {
+    options: ParseOptions,
+}
+
+impl ParserBuilder {
+    pub fn new() -> ParserBuilder {
+        ParserBuilder::default()
+    }
+
+    pub fn raw_numbers(mut self, raw_numbers: bool) -> ParserBuilder {
+        self.options.raw_numbers = raw_numbers;
+        self
+    }
+
+    pub fn number_overflow(mut self, number_overflow: NumberOverflow) -> ParserBuilder {
+        self.options.number_overflow = number_overflow;
+        self
+    }
+
+    pub fn lone_surrogates(mut self, lone_surrogates: SurrogatePolicy) -> ParserBuilder {
+        self.options.lone_surrogates = lone_surrogates;
+        self
+    }
+
+    pub fn non_finite(mut self, non_finite: NonFinite) -> ParserBuilder {
+        self.options.non_finite = non_finite;
+        self
+    }
+
+    pub fn comments(mut self, comments: CommentStyle) -> ParserBuilder {
+        self.options.comments = comments;
+        self
+    }
+
+    pub fn single_quotes(mut self, allow: bool) -> ParserBuilder {
+        self.options.allow_single_quotes = allow;
+        self
+    }
+
+    pub fn unquoted_keys(mut self, allow: bool) -> ParserBuilder {
+        self.options.allow_unquoted_keys = allow;
+        self
+    }
+
+    pub fn trailing_commas(mut self, allow: bool) -> ParserBuilder {
+        self.options.allow_trailing_commas = allow;
+        self
+    }
+
+    pub fn missing_commas(mut self, allow: bool) -> ParserBuilder {
+        self.options.allow_missing_commas = allow;
+        self
+    }
+
+    pub fn max_string_len(mut self, max: usize) -> ParserBuilder {
+        self.options.max_string_len = Some(max);
+        self
+    }
+
+    pub fn hasher(mut self, hasher: HasherKind) -> ParserBuilder {
+        self.options.hasher = hasher;
+        self
+    }
+
+    pub fn max_depth(mut self, max: usize) -> ParserBuilder {
+        self.options.max_depth = Some(max);
+        self
+    }
+
+    pub fn max_total_bytes(mut self, max: usize) -> ParserBuilder {
+        self.options.max_total_bytes = Some(max);
+        self
+    }
+
+    pub fn max_elements(mut self, max: usize) -> ParserBuilder {
+        self.options.max_elements = Some(max);
+        self
+    }
+
+    pub fn intern_strings(mut self, intern: bool) -> ParserBuilder {
+        self.options.intern_strings = intern;
+        self
+    }
+
+    pub fn top_level_scalar(mut self, allow: bool) -> ParserBuilder {
+        self.options.allow_top_level_scalar = allow;
+        self
+    }
+
+    #[cfg(feature = "normalize-keys")]
+    pub fn normalize_keys(mut self, kind: NfKind) -> ParserBuilder {
+        self.options.normalize_keys = Some(kind);
+        self
+    }
+
+    /// The options configured so far.
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    pub fn parse(&self, source: &str) -> Result<JsonNode, ParseError> {
+        parse_with_options(source, &self.options)
+    }
+}
+
+/// A Unicode normalization form for `ParseOptions::normalize_keys`.
+#[cfg(feature = "normalize-keys")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NfKind {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+#[cfg(feature = "normalize-keys")]
+impl NfKind {
+    fn normalize(self, s: &str) -> String {
+        use unicode_normalization::UnicodeNormalization;
+
+        match self {
+            NfKind::Nfc => s.nfc().collect(),
+            NfKind::Nfd => s.nfd().collect(),
+            NfKind::Nfkc => s.nfkc().collect(),
+            NfKind::Nfkd => s.nfkd().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommentStyle {
+    /// Comments are a syntax error, as in strict JSON.
+    #[default]
+    None,
+    /// `// line` and `/* block */` comments, as in JSONC.
+    SlashSlash,
+    /// `# line` comments, as in many config dialects.
+    Hash,
+    /// Both of the above.
+    Both,
+}
+
+impl CommentStyle {
+    pub(crate) fn slash(self) -> bool {
+        matches!(self, CommentStyle::SlashSlash | CommentStyle::Both)
+    }
+
+    pub(crate) fn hash(self) -> bool {
+        matches!(self, CommentStyle::Hash | CommentStyle::Both)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberOverflow {
+    /// Accept the `f64` result like JavaScript does: `1e400` becomes infinity and `1e-400` becomes zero.
+    #[default]
+    Lenient,
+    /// Reject numbers that overflow to infinity or underflow to zero.
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SurrogatePolicy {
+    /// Fail with "lone surrogate".
+    #[default]
+    Error,
+    /// Decode it as U+FFFD, the Unicode replacement character.
+    ReplacementChar,
+    /// Keep the six characters of the escape, e.g. `\ud800`, as they are.
+    Passthrough,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFinite {
+    /// Reject `NaN` and `Infinity`, as strict JSON does.
+    #[default]
+    Reject,
+    /// Accept `NaN`, `Infinity`, `+Infinity` and `-Infinity`, as written by
+    /// JSON5 and JavaScript.
+    Allow,
+    /// Like `Allow`, but in any letter case, e.g. `infinity` or `nan`.
+    AllowAnyCase,
+}
+
+impl NonFinite {
+    /// The value of `text` if it spells NaN or an infinity, optionally signed.
+    fn value(self, text: &str) -> Option<f64> {
+        let (sign, word) = match text.as_bytes().first() {
+            Some(b'-') => (-1.0, &text[1..]),
+            Some(b'+') => (1.0, &text[1..]),
+            _ => (1.0, text),
+        };
+        let spells = |name: &str| match self {
+            NonFinite::Allow => word == name,
+            NonFinite::Reject | NonFinite::AllowAnyCase => word.eq_ignore_ascii_case(name),
+        };
+
+        if spells("Infinity") {
+            Some(sign * f64::INFINITY)
+        } else if spells("NaN") {
+            Some(f64::NAN)
+        } else {
+            None
+        }
+    }
+}
+
+pub(crate) struct JsonParser<'a> {
+    pub(crate) source: &'a str,
     tokenizer: Tokenizer<'a>,
-    buffer: VecDeque<Token<'a>>,
+    /// The single token of lookahead filled by `peek`; the grammar never needs more.
+    lookahead: Option<Token<'a>>,
+    /// Byte offsets of the brackets of the arrays and objects being parsed.
+    pub(crate) open: Vec<usize>,
+    /// The approximate size of the tree built so far, for `max_total_bytes`.
+    allocated: usize,
+    /// Array elements and object members read so far, for `max_elements`.
+    elements: usize,
+    /// Set once the tokenizer has returned `None`.
+    eof: bool,
+    /// The errors skipped so far by `parse_recoverable`; `None` stops at the first.
+    errors: Option<Vec<ParseError>>,
+    /// The pointer to the value being parsed and the scalar spans recorded
+    /// so far, for `parse_with_spans`; `None` records nothing.
+    spans: Option<(String, SourceSpans<'a>)>,
+    #[cfg(feature = "trace")]
+    trace: ParseTrace,
+    options: ParseOptions,
 }
 
-struct JsonError<'a> {
-    message: &'a str,
+pub(crate) struct JsonError<'a> {
+    message: Cow<'static, str>,
     token: Option<Token<'a>>,
 }
 
 impl<'a> JsonParser<'a> {
     pub fn new(source: &'a str) -> JsonParser<'a> {
+        JsonParser::with_options(source, ParseOptions::default())
+    }
+
+    pub fn with_options(source: &'a str, options: ParseOptions) -> JsonParser<'a> {
+        let tokenizer_options = TokenizerOptions {
+            comments: options.comments,
+            single_quotes: options.allow_single_quotes,
+            emit_comments: false,
+            identifiers: options.allow_unquoted_keys,
+        };
         JsonParser {
-            tokenizer: Tokenizer::new(source),
-            buffer: VecDeque::new(),
+            source,
+            tokenizer: Tokenizer::new(source).with_options(tokenizer_options),
+            lookahead: None,
+            open: vec![],
+            allocated: 0,
+            elements: 0,
+            eof: false,
+            errors: None,
+            spans: None,
+            #[cfg(feature = "trace")]
+            trace: ParseTrace::default(),
+            options,
         }
     }
 
-    pub fn parse(&mut self) -> Result<JsonNode, String> {
+    pub fn parse(&mut self) -> Result<JsonNode, ParseError> {
+        if self.peek().is_none() {
+            return Err(ParseError::empty());
+        }
+
+        if !self.options.allow_top_level_scalar {
+            if let Some(token) = self.peek() {
+                if matches!(
+                    token.token_type,
+                    TokenType::String { .. }
+                        | TokenType::Number { .. }
+                        | TokenType::True
+                        | TokenType::False
+                        | TokenType::Null
+                ) {
+                    let e = JsonError {
+                        message: "top-level value must be object or array".into(),
+                        token: Some(token.clone()),
+                    };
+                    return Err(self.error(e));
+                }
+            }
+        }
+
         match self.value() {
             Ok(json) => Ok(json),
-            Err(e) => match e.token {
-                Some(token) => Err(format!(
-                    "error: {}, at index: {}, line: {}",
-                    e.message, token.index, token.line
-                )),
-                None => Err(format!("error: {}", e.message)),
-            },
+            Err(e) => Err(self.error(e)),
+        }
+    }
+
+    pub(crate) fn error(&self, e: JsonError) -> ParseError {
+        match e.token {
+            // a comment can be cut off wherever whitespace may go, and is the
+            // problem whatever was expected there
+            Some(Token {
+                token_type:
+                    TokenType::Error {
+                        kind: kind @ LexErrorKind::UnterminatedComment,
+                        ..
+                    },
+                index,
+                line,
+            }) => ParseError::at(kind.message(), self.source, index, line),
+            Some(token) => ParseError::at(&e.message, self.source, token.index, token.line),
+            // only running out of input leaves an error without a token
+            None => ParseError::eof(&e.message),
+        }
+    }
+
+    /// Records `e` and carries on when recovering; returns it otherwise.
+    fn report(&mut self, e: JsonError<'a>) -> Result<(), JsonError<'a>> {
+        if self.errors.is_none() {
+            return Err(e);
+        }
+        let error = self.error(e);
+        self.errors.as_mut().unwrap().push(error);
+        Ok(())
+    }
+
+    /// Reports `e` and skips ahead to the next `,`, `]` or `}` so the
+    /// enclosing container can carry on. An error at a bracket or at the end
+    /// of input leaves no safe place to resume, so it is returned instead.
+    fn recover(&mut self, e: JsonError<'a>) -> Result<(), JsonError<'a>> {
+        let token = match (&self.errors, &e.token) {
+            (Some(_), Some(token)) => token.clone(),
+            _ => return Err(e),
+        };
+        match token.token_type {
+            TokenType::LeftSquareBracket
+            | TokenType::LeftCurlyBracket
+            | TokenType::RightSquareBracket
+            | TokenType::RightCurlyBracket => return Err(e),
+            // the comma itself was the problem, and is where to resume
+            TokenType::Comma if self.lookahead.is_none() => self.lookahead = Some(token),
+            _ => {
+                let at_boundary = self.lookahead.as_ref().is_some_and(|token| {
+                    matches!(
+                        token.token_type,
+                        TokenType::Comma
+                            | TokenType::RightSquareBracket
+                            | TokenType::RightCurlyBracket
+                    )
+                });
+                if !at_boundary {
+                    self.lookahead = None;
+                    self.tokenizer.recover();
+                }
+            }
+        }
+        self.report(e)
+    }
+
+    /// Parses the value under `segment` of the current value, keeping track
+    /// of its pointer when recording spans. `segment` is only called then.
+    fn value_at(&mut self, segment: impl FnOnce() -> String) -> Result<JsonNode, JsonError<'a>> {
+        let Some((pointer, _)) = &mut self.spans else {
+            return self.value();
+        };
+        let len = pointer.len();
+        pointer.push('/');
+        pointer.push_str(&segment());
+
+        let value = self.value();
+        if let Some((pointer, _)) = &mut self.spans {
+            pointer.truncate(len);
         }
+        value
     }
 
-    fn value(&mut self) -> Result<JsonNode, JsonError<'a>> {
+    pub(crate) fn value(&mut self) -> Result<JsonNode, JsonError<'a>> {
         let tokenopt = self.advance();
+        if let Some(token) = &tokenopt {
+            self.charge(core::mem::size_of::<JsonNode>(), token)?;
+        }
+        if let (Some((pointer, spans)), Some(token)) = (&mut self.spans, &tokenopt) {
+            let scalar = matches!(
+                token.token_type,
+                TokenType::Number { .. }
+                    | TokenType::String { .. }
+                    | TokenType::True
+                    | TokenType::False
+                    | TokenType::Null
+                    // NaN and Infinity, when allowed
+                    | TokenType::Error {
+                        kind: LexErrorKind::UnknownKeyword,
+                        ..
+                    }
+            );
+            if scalar {
+                let text = &self.source[token.index..token.end()];
+                spans.spans.insert(pointer.clone(), text);
+            }
+        }
         match tokenopt {
             Some(token) => match token.token_type {
-                TokenType::Number { text } => Ok(JsonParser::number(&text)),
-                TokenType::String { text } => JsonParser::string(&text),
+                TokenType::Number { text } => match self.number(text) {
+                    Ok(n) => Ok(n),
+                    Err(message) => Err(JsonError {
+                        message: message.into(),
+                        token: Some(token),
+                    }),
+                },
+                TokenType::String { text } => match self.escape(text) {
+                    Ok(s) => {
+                        self.charge(s.len(), &token)?;
+                        Ok(JsonNode::String(s))
+                    }
+                    Err(message) => Err(JsonError {
+                        message: message.into(),
+                        token: Some(token),
+                    }),
+                },
                 TokenType::True => Ok(JsonNode::Bool(true)),
                 TokenType::False => Ok(JsonNode::Bool(false)),
                 TokenType::Null => Ok(JsonNode::Null),
-                TokenType::LeftSquareBracket => self.array(),
-                TokenType::LeftCurlyBracket => self.object(),
+                TokenType::LeftSquareBracket => self.nested(token, JsonParser::array),
+                TokenType::LeftCurlyBracket => self.nested(token, JsonParser::object),
                 TokenType::RightSquareBracket => Err(JsonError {
-                    message: "Unexpected ]",
+                    message: "Unexpected ]".into(),
                     token: Some(token),
                 }),
                 TokenType::RightCurlyBracket => Err(JsonError {
-                    message: "Unexpected [",
+                    message: "Unexpected }".into(),
                     token: Some(token),
                 }),
                 TokenType::Comma => Err(JsonError {
-                    message: "Unexpected comma",
+                    message: "Unexpected comma".into(),
                     token: Some(token),
                 }),
                 TokenType::Colon => Err(JsonError {
-                    message: "Unexpected colon",
+                    message: "Unexpected colon".into(),
                     token: Some(token),
                 }),
-                TokenType::Error { message, .. } => Err(JsonError {
-                    message,
+                TokenType::Error {
+                    kind: LexErrorKind::UnknownKeyword,
+                    text,
+                } if self.options.non_finite.value(text).is_some() => {
+                    match self.options.non_finite {
+                        // any spelling in any case gets the clearer message
+                        NonFinite::Reject => Err(JsonError {
+                            message: "NaN and Infinity are not allowed".into(),
+                            token: Some(token),
+                        }),
+                        _ => Ok(JsonNode::Number(Number::new(
+                            self.options.non_finite.value(text).unwrap(),
+                        ))),
+                    }
+                }
+                TokenType::Error { kind, .. } => Err(JsonError {
+                    message: kind.message().into(),
+                    token: Some(token),
+                }),
+                // the parser never asks the tokenizer for comment tokens
+                TokenType::LineComment { .. } | TokenType::BlockComment { .. } => Err(JsonError {
+                    message: "Unexpected comment".into(),
                     token: Some(token),
                 }),
             },
             None => Err(JsonError {
-                message: "eof",
+                message: "eof".into(),
                 token: None,
             }),
         }
     }
 
+    /// Adds `bytes` to the running size of the tree, failing at `token` once
+    /// it is over `max_total_bytes`.
+    fn charge(&mut self, bytes: usize, token: &Token<'a>) -> Result<(), JsonError<'a>> {
+        self.allocated += bytes;
+        match self.options.max_total_bytes {
+            Some(max) if self.allocated > max => Err(JsonError {
+                message: "memory budget exceeded".into(),
+                token: Some(token.clone()),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts one more array element or object member, failing at `token`
+    /// once there are more than `max_elements`.
+    fn count_element(&mut self, token: &Token<'a>) -> Result<(), JsonError<'a>> {
+        self.elements += 1;
+        match self.options.max_elements {
+            Some(max) if self.elements > max => Err(JsonError {
+                message: "too many elements".into(),
+                token: Some(token.clone()),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The error for a bracket that closes a different kind of container than
+    /// the innermost open one.
+    fn mismatch(&self, token: Token<'a>) -> JsonError<'a> {
+        let (expected, container, found) = match token.token_type {
+            TokenType::RightCurlyBracket => (']', "array", '}'),
+            _ => ('}', "object", ']'),
+        };
+        let message = format!(
+            "mismatched bracket: expected '{}' to close {} opened at index {}, found '{}'",
+            expected,
+            container,
+            self.open.last().copied().unwrap_or(0),
+            found
+        );
+        JsonError {
+            message: message.into(),
+            token: Some(token),
+        }
+    }
+
+    /// Parses the container opened by `token`, enforcing `max_depth`.
+    fn nested(
+        &mut self,
+        token: Token<'a>,
+        container: fn(&mut JsonParser<'a>) -> Result<JsonNode, JsonError<'a>>,
+    ) -> Result<JsonNode, JsonError<'a>> {
+        if self
+            .options
+            .max_depth
+            .is_some_and(|max| self.open.len() >= max)
+        {
+            return Err(JsonError {
+                message: "nesting too deep".into(),
+                token: Some(token),
+            });
+        }
+
+        self.open.push(token.index);
+        #[cfg(feature = "trace")]
+        {
+            self.trace.max_depth = self.trace.max_depth.max(self.open.len());
+        }
+        let result = container(self);
+        self.open.pop();
+        result
+    }
+
     fn object(&mut self) -> Result<JsonNode, JsonError<'a>> {
-        let mut obj: HashMap<String, JsonNode> = HashMap::new();
-        loop {
-            let token = self.advance();
-            let string = match token.clone() {
-                Some(token) => match token.token_type {
-                    TokenType::String { text, .. } => JsonParser::escape(text.clone()),
-                    TokenType::RightCurlyBracket { .. } => break,
-                    _ => {
-                        return Err(JsonError {
-                            message: "object key is not string",
-                            token: Some(token),
-                        })
-                    }
-                },
-                None => {
-                    return Err(JsonError {
-                        message: "eof",
-                        token: None,
-                    })
+        let mut obj = new_map(self.options.hasher);
+        let mut after_comma = false;
+        'members: loop {
+            match self.member(after_comma) {
+                Ok(Some((key, value))) => {
+                    obj.insert(key, value);
                 }
-            };
-
-            let key = match string {
-                Ok(s) => s,
-                Err(_) => {
-                    return Err(JsonError {
-                        message: "invalid string",
-                        token,
-                    })
+                Ok(None) => break,
+                Err(e) => self.recover(e)?,
+            }
+            loop {
+                match self.member_separator() {
+                    Ok(true) => break,
+                    Ok(false) => break 'members,
+                    Err(e) => self.recover(e)?,
                 }
-            };
+            }
+            after_comma = true;
+        }
 
-            match self.advance() {
-                Some(token) => match token.token_type {
-                    TokenType::Colon { .. } => {}
-                    _ => {
+        Ok(JsonNode::Object(obj))
+    }
+
+    /// Parses one `key: value` member of an object whose `{` has been
+    /// consumed, or consumes the closing `}` and returns `None`.
+    pub(crate) fn member(
+        &mut self,
+        after_comma: bool,
+    ) -> Result<Option<(String, JsonNode)>, JsonError<'a>> {
+        let token = self.advance();
+        let string = match token.clone() {
+            Some(token) => match token.token_type {
+                TokenType::String { text, .. } => self.escape(text),
+                TokenType::RightCurlyBracket { .. } => {
+                    if after_comma && !self.options.allow_trailing_commas {
+                        self.report(JsonError {
+                            message: "trailing comma in object".into(),
+                            token: Some(token),
+                        })?;
+                    }
+                    return Ok(None);
+                }
+                TokenType::RightSquareBracket => return Err(self.mismatch(token)),
+                _ if self.options.allow_unquoted_keys => match JsonParser::bare_key(&token) {
+                    Some(key) => Ok(key.to_string()),
+                    None => {
                         return Err(JsonError {
-                            message: "expect :",
+                            message: format!("object key must be a string, found {}", token).into(),
                             token: Some(token),
                         })
                     }
                 },
-                None => {
+                _ => {
                     return Err(JsonError {
-                        message: "expect :",
-                        token: None,
+                        message: format!("object key must be a string, found {}", token).into(),
+                        token: Some(token),
                     })
                 }
+            },
+            None => {
+                return Err(JsonError {
+                    message: "eof".into(),
+                    token: None,
+                })
             }
+        };
 
-            let value = match self.value() {
-                Ok(v) => v,
-                Err(e) => return Err(e),
-            };
-
-            obj.insert(key, value);
+        let key = match string {
+            Ok(s) => s,
+            Err(message) => {
+                return Err(JsonError {
+                    message: message.into(),
+                    token,
+                })
+            }
+        };
+        #[cfg(feature = "normalize-keys")]
+        let key = match self.options.normalize_keys {
+            Some(kind) => kind.normalize(&key),
+            None => key,
+        };
+        if let Some(token) = &token {
+            self.count_element(token)?;
+            self.charge(core::mem::size_of::<String>() + key.len(), token)?;
+        }
 
-            match self.advance() {
-                Some(token) => match token.token_type {
-                    TokenType::RightCurlyBracket { .. } => break,
-                    TokenType::Comma { .. } => continue,
-                    _ => {
-                        return Err(JsonError {
-                            message: "expected comma or object close",
-                            token: None,
-                        })
-                    }
-                },
-                None => {
+        match self.advance() {
+            Some(token) => match token.token_type {
+                TokenType::Colon { .. } => {}
+                _ => {
                     return Err(JsonError {
-                        message: "unexpected eof",
-                        token: None,
+                        message: "expect :".into(),
+                        token: Some(token),
                     })
                 }
+            },
+            None => {
+                return Err(JsonError {
+                    message: "expect :".into(),
+                    token: None,
+                })
             }
         }
 
-        Ok(JsonNode::Object(obj))
+        if let Some(token) = self.peek() {
+            if token.token_type == TokenType::RightCurlyBracket {
+                // the member is dropped and its object closed
+                let token = self.advance();
+                self.report(JsonError {
+                    message: "expected a value".into(),
+                    token,
+                })?;
+                return Ok(None);
+            }
+        }
+
+        let value = self.value_at(|| escape_token(&key))?;
+        Ok(Some((key, value)))
+    }
+
+    /// Consumes the `,` or `}` after an object member, returning whether
+    /// another member follows.
+    pub(crate) fn member_separator(&mut self) -> Result<bool, JsonError<'a>> {
+        match self.advance() {
+            Some(token) => match token.token_type {
+                TokenType::RightCurlyBracket { .. } => Ok(false),
+                TokenType::Comma { .. } => Ok(true),
+                TokenType::RightSquareBracket => Err(self.mismatch(token)),
+                _ => Err(JsonError {
+                    message: "expected comma or object close".into(),
+                    token: Some(token),
+                }),
+            },
+            None => Err(JsonError {
+                message: "unexpected eof".into(),
+                token: None,
+            }),
+        }
     }
 
     fn array(&mut self) -> Result<JsonNode, JsonError<'a>> {
         let mut arr: Vec<JsonNode> = vec![];
-        loop {
+        let mut after_comma = false;
+        'elements: loop {
             let token = self.peek();
             if token.is_none() {
                 return Err(JsonError {
-                    message: "eof",
+                    message: "eof".into(),
                     token: None,
                 });
             }
 
             let value = match token.unwrap().token_type {
                 TokenType::RightSquareBracket { .. } => {
-                    self.advance();
+                    let token = self.advance();
+                    if after_comma && !self.options.allow_trailing_commas {
+                        self.report(JsonError {
+                            message: "trailing comma in array".into(),
+                            token,
+                        })?;
+                    }
                     break;
                 }
-                _ => self.value(),
+                TokenType::RightCurlyBracket => {
+                    let token = self.advance().unwrap();
+                    return Err(self.mismatch(token));
+                }
+                _ => {
+                    let token = token.unwrap().clone();
+                    self.count_element(&token)
+                        .and_then(|_| self.value_at(|| arr.len().to_string()))
+                }
             };
 
             match value {
                 Ok(value) => arr.push(value),
-                Err(e) => return Err(e),
+                Err(e) => self.recover(e)?,
             }
 
-            let token = self.advance();
-
-            match token {
-                Some(token) => match token.token_type {
-                    TokenType::RightSquareBracket { .. } => break,
-                    TokenType::Comma { .. } => continue,
-                    _ => {
-                        // return Err(JsonError {
-                        //     message: "expected comma or end of array",
-                        //     token: Some(token),
-                        // })
-                        todo!()
+            loop {
+                let token = self.advance();
+
+                match token {
+                    Some(token) => match token.token_type {
+                        TokenType::RightSquareBracket { .. } => break 'elements,
+                        TokenType::RightCurlyBracket => return Err(self.mismatch(token)),
+                        TokenType::Comma { .. } => {
+                            after_comma = true;
+                            continue 'elements;
+                        }
+                        _ if self.options.allow_missing_commas
+                            && JsonParser::starts_value(&token) =>
+                        {
+                            // treat the gap as an implicit comma and parse the token as the next element
+                            self.lookahead = Some(token);
+                            after_comma = false;
+                            continue 'elements;
+                        }
+                        _ => self.recover(JsonError {
+                            message: "expected comma or end of array".into(),
+                            token: Some(token),
+                        })?,
+                    },
+                    None => {
+                        return Err(JsonError {
+                            message: "unexpected eof".into(),
+                            token: None,
+                        })
                     }
-                },
-                None => {
-                    return Err(JsonError {
-                        message: "unexpected eof",
-                        token: None,
-                    })
                 }
             }
         }
@@ -209,111 +1042,183 @@ impl<'a> JsonParser<'a> {
         Ok(JsonNode::Array(arr))
     }
 
-    fn string(s: &str) -> Result<JsonNode, JsonError> {
-        match JsonParser::escape(s) {
-            Ok(s) => Ok(JsonNode::String(s)),
-            Err(message) => Err(JsonError {
-                message,
-                token: None,
-            }),
+    fn escape(&mut self, s: &str) -> Result<String, &'static str> {
+        #[cfg(feature = "trace")]
+        {
+            self.trace.strings_escaped += 1;
         }
-    }
-
-    fn escape(s: &str) -> Result<String, &str> {
-        let mut chars = s.chars().peekable();
-        let mut escaped = String::with_capacity(s.len());
-
-        chars.next(); // consume first "
 
-        loop {
-            let c = match chars.next() {
-                Some(c) => c,
-                None => return Err("unexpected string end"),
-            };
+        // the tokenizer only emits string tokens that are wrapped in quotes
+        let quote = s.chars().next().unwrap();
+        let body = &s[1..s.len() - 1];
 
-            if c == '\"' {
-                break;
+        // checked on the raw body so an oversized string is never allocated
+        if let Some(max) = self.options.max_string_len {
+            if body.len() > max {
+                return Err("string too long");
             }
+        }
 
-            if c != '\\' {
-                escaped.push(c);
-                continue;
-            }
+        unescape_quoted(body, quote, self.options.lone_surrogates)
+    }
 
-            match chars.next() {
-                Some(c) => match c {
-                    '\"' => escaped.push('\"'),
-                    '\\' => escaped.push('\\'),
-                    '/' => escaped.push('/'),
-                    'n' => escaped.push('\n'),
-                    'b' => {
-                        escaped.pop();
-                    }
-                    'f' => escaped.push(char::from_u32(0xC).unwrap()),
-                    'r' => escaped.push('\r'),
-                    't' => escaped.push('\t'),
-                    'u' => {
-                        let mut hexs = String::with_capacity(4);
-
-                        for _ in 0..4 {
-                            match chars.next() {
-                                Some(c) => hexs.push(c),
-                                None => return Err("unexpected eof"),
-                            };
-                        }
-                        let x = match u32::from_str_radix(&hexs, 16) {
-                            Ok(n) => n,
-                            Err(_) => return Err("parse \\u error"),
-                        };
-                        match char::from_u32(x) {
-                            Some(c) => escaped.push(c),
-                            None => return Err("parse \\u error"),
-                        }
-                    }
-                    _ => unreachable!(),
-                },
-                None => return Err("invalid token"),
-            };
+    /// The key text of an unquoted object key, if `token` is one.
+    fn bare_key(token: &Token<'a>) -> Option<&'a str> {
+        match token.token_type {
+            TokenType::True => Some("true"),
+            TokenType::False => Some("false"),
+            TokenType::Null => Some("null"),
+            TokenType::Error {
+                kind: LexErrorKind::UnknownKeyword,
+                text,
+            } => {
+                let mut chars = text.chars();
+                let first = chars.next()?;
+                let valid = (first.is_alphabetic() || first == '_' || first == '$')
+                    && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+                if valid {
+                    Some(text)
+                } else {
+                    None
+                }
+            }
+            _ => None,
         }
+    }
 
-        Ok(escaped)
+    fn starts_value(token: &Token<'a>) -> bool {
+        matches!(
+            token.token_type,
+            TokenType::String { .. }
+                | TokenType::Number { .. }
+                | TokenType::True
+                | TokenType::False
+                | TokenType::Null
+                | TokenType::LeftSquareBracket
+                | TokenType::LeftCurlyBracket
+        )
     }
 
-    fn number(s: &str) -> JsonNode {
-        JsonNode::Number(s.parse::<f64>().unwrap())
+    fn number(&self, s: &str) -> Result<JsonNode, &'static str> {
+        if self.options.raw_numbers {
+            return Ok(JsonNode::RawNumber(s.to_string()));
+        }
+
+        let number = parse_number(s).ok_or("invalid number")?;
+        let n = number.as_f64();
+        if self.options.number_overflow == NumberOverflow::Error && number.integer().is_none() {
+            if n.is_infinite() {
+                return Err("number overflow");
+            }
+
+            let mantissa = s.split(['e', 'E']).next().unwrap_or(s);
+            if n == 0.0 && mantissa.bytes().any(|b| (b'1'..=b'9').contains(&b)) {
+                return Err("number underflow");
+            }
+        }
+
+        Ok(JsonNode::Number(number))
     }
 
-    fn advance(&mut self) -> Option<Token<'a>> {
-        if !self.buffer.is_empty() {
-            let token = self.buffer.pop_front();
-            return token;
+    fn position(&self) -> usize {
+        match &self.lookahead {
+            Some(token) => token.index,
+            None => self.tokenizer.position(),
         }
+    }
 
-        self.tokenizer.next()
+    pub(crate) fn advance(&mut self) -> Option<Token<'a>> {
+        match self.lookahead.take() {
+            Some(token) => Some(token),
+            None => self.next_token(),
+        }
     }
 
     fn peek(&mut self) -> Option<&Token<'a>> {
+        if self.lookahead.is_none() {
+            self.lookahead = self.next_token();
+        }
+        self.lookahead.as_ref()
+    }
+
+    /// Pulls the next token, remembering once the tokenizer has run dry so
+    /// later calls at the end of input don't touch it again.
+    fn next_token(&mut self) -> Option<Token<'a>> {
+        if self.eof {
+            return None;
+        }
         let token = self.tokenizer.next();
-        match token {
-            Some(token) => {
-                self.buffer.push_back(token);
-                self.buffer.back()
-            }
-            None => None,
+        self.eof = token.is_none();
+        #[cfg(feature = "trace")]
+        {
+            self.trace.tokens += token.is_some() as usize;
         }
+        token
     }
 }
 
+/// A parsed JSON value.
+///
+/// More variants may be added in future releases, so matches outside this
+/// crate need a wildcard arm. The `as_*` and `is_*` accessors are the stable
+/// way to inspect a node; `as_f64`, for instance, already covers both
+/// `Number` and `RawNumber`.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[non_exhaustive]
 pub enum JsonNode {
     String(String),
-    Number(f64),
+    Number(Number),
+    RawNumber(String),
     Array(Vec<JsonNode>),
-    Object(HashMap<String, JsonNode>),
+    Object(Map),
     Bool(bool),
+    #[default]
     Null,
 }
 
 impl JsonNode {
+    /// Returns an empty object.
+    pub fn object() -> JsonNode {
+        JsonNode::Object(Map::default())
+    }
+
+    /// Returns an empty array.
+    pub fn array() -> JsonNode {
+        JsonNode::Array(vec![])
+    }
+
+    /// Inserts `value` under `key`, returning the value previously stored there.
+    ///
+    /// Panics if this node is not an object.
+    pub fn insert(&mut self, key: &str, value: JsonNode) -> Option<JsonNode> {
+        match self {
+            JsonNode::Object(map) => map.insert(key.to_string(), value),
+            _ => panic!("cannot insert a key into a non-object node"),
+        }
+    }
+
+    /// Appends `value` to the end of this array.
+    ///
+    /// Panics if this node is not an array.
+    pub fn push(&mut self, value: JsonNode) {
+        match self {
+            JsonNode::Array(vec) => vec.push(value),
+            _ => panic!("cannot push onto a non-array node"),
+        }
+    }
+
+    /// The JSON name of this node's type, e.g. `"number"` or `"object"`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonNode::String(_) => "string",
+            JsonNode::Number(_) | JsonNode::RawNumber(_) => "number",
+            JsonNode::Array(_) => "array",
+            JsonNode::Object(_) => "object",
+            JsonNode::Bool(_) => "boolean",
+            JsonNode::Null => "null",
+        }
+    }
+
     pub fn as_string(&self) -> Option<&String> {
         match self {
             JsonNode::String(s) => Some(s),
@@ -321,13 +1226,62 @@ impl JsonNode {
         }
     }
 
-    pub fn as_number(&self) -> Option<&f64> {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonNode::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
         match self {
             JsonNode::Number(n) => Some(n),
             _ => None,
         }
     }
 
+    pub fn as_raw_number(&self) -> Option<&str> {
+        match self {
+            JsonNode::RawNumber(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the numeric value, parsing the text of a `RawNumber` on demand.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonNode::Number(n) => Some(n.as_f64()),
+            JsonNode::RawNumber(s) => s.parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an `i64` if it is an integer in range; raw
+    /// numbers are read exactly instead of going through `f64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        i64::try_from(self).ok()
+    }
+
+    /// Like `as_i64`, for non-negative integers up to `u64::MAX`.
+    pub fn as_u64(&self) -> Option<u64> {
+        u64::try_from(self).ok()
+    }
+
+    /// The string value, or `default` if this node is not a string.
+    pub fn string_or<'s>(&'s self, default: &'s str) -> &'s str {
+        self.as_string().map_or(default, |s| s.as_str())
+    }
+
+    /// The numeric value (see `as_f64`), or `default` if this node is not a number.
+    pub fn number_or(&self, default: f64) -> f64 {
+        self.as_f64().unwrap_or(default)
+    }
+
+    /// The boolean value, or `default` if this node is not a boolean.
+    pub fn bool_or(&self, default: bool) -> bool {
+        self.as_bool().copied().unwrap_or(default)
+    }
+
     pub fn as_bool(&self) -> Option<&bool> {
         match self {
             JsonNode::Bool(b) => Some(b),
@@ -342,19 +1296,223 @@ impl JsonNode {
         }
     }
 
-    pub fn as_map(&self) -> Option<&HashMap<String, JsonNode>> {
+    pub fn as_map(&self) -> Option<&Map> {
         match self {
             JsonNode::Object(map) => Some(map),
             _ => None,
         }
     }
 
+    pub fn as_null(&self) -> Option<()> {
+        match self {
+            JsonNode::Null => Some(()),
+            _ => None,
+        }
+    }
+
     pub fn is_null(&self) -> bool {
         match self {
             JsonNode::Null => true,
             _ => false,
         }
     }
+
+    /// Loose, JavaScript-style truthiness for porting scripting logic; JSON
+    /// itself has no such notion. `false`, `null`, zero, NaN, `""` and empty
+    /// arrays and objects are falsy, everything else truthy. Unlike in
+    /// JavaScript, empty containers count as falsy.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            JsonNode::Bool(b) => *b,
+            JsonNode::Null => false,
+            JsonNode::Number(_) | JsonNode::RawNumber(_) => {
+                self.as_f64().is_some_and(|n| n != 0.0 && !n.is_nan())
+            }
+            JsonNode::String(s) => !s.is_empty(),
+            JsonNode::Array(vec) => !vec.is_empty(),
+            JsonNode::Object(map) => !map.is_empty(),
+        }
+    }
+
+    pub fn is_string(&self) -> bool {
+        matches!(self, JsonNode::String(_))
+    }
+
+    /// True for every number representation, parsed or raw.
+    pub fn is_number(&self) -> bool {
+        matches!(self, JsonNode::Number(_) | JsonNode::RawNumber(_))
+    }
+
+    pub fn is_bool(&self) -> bool {
+        matches!(self, JsonNode::Bool(_))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, JsonNode::Array(_))
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self, JsonNode::Object(_))
+    }
+
+    /// Replaces this node with `Null` and returns the previous value, like `Option::take`.
+    pub fn take(&mut self) -> JsonNode {
+        core::mem::replace(self, JsonNode::Null)
+    }
+
+    /// Searches this node and all of its descendants for a value equal to `needle`.
+    pub fn contains_value(&self, needle: &JsonNode) -> bool {
+        if self == needle {
+            return true;
+        }
+
+        match self {
+            JsonNode::Array(vec) => vec.iter().any(|v| v.contains_value(needle)),
+            JsonNode::Object(map) => map.values().any(|v| v.contains_value(needle)),
+            _ => false,
+        }
+    }
+
+    /// Counts this node and its descendants for which `pred` returns true.
+    pub fn count_matches(&self, pred: impl Fn(&JsonNode) -> bool) -> usize {
+        self.count_matches_with(&pred)
+    }
+
+    fn count_matches_with(&self, pred: &impl Fn(&JsonNode) -> bool) -> usize {
+        let children = match self {
+            JsonNode::Array(vec) => vec.iter().map(|v| v.count_matches_with(pred)).sum(),
+            JsonNode::Object(map) => map.values().map(|v| v.count_matches_with(pred)).sum(),
+            _ => 0,
+        };
+        children + pred(self) as usize
+    }
+
+    /// Like `==`, but numbers (raw ones included) compare equal when they are
+    /// within `epsilon` of each other, at any depth.
+    pub fn approx_eq(&self, other: &JsonNode, epsilon: f64) -> bool {
+        match (self, other) {
+            (JsonNode::Array(a), JsonNode::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.approx_eq(y, epsilon))
+            }
+            (JsonNode::Object(a), JsonNode::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(k, x)| b.get(k).is_some_and(|y| x.approx_eq(y, epsilon)))
+            }
+            _ if self.is_number() && other.is_number() => match (self.as_f64(), other.as_f64()) {
+                (Some(x), Some(y)) => x == y || (x - y).abs() <= epsilon,
+                _ => self == other,
+            },
+            _ => self == other,
+        }
+    }
+
+    /// Rewrites equivalent spellings the same way throughout the tree, so
+    /// equal documents serialize byte for byte alike: object members are
+    /// sorted by key, -0 becomes 0 and raw number text is put in one form
+    /// (see `normalize_with` to also trim trailing zeros).
+    pub fn normalize(&mut self) {
+        self.normalize_with(false);
+    }
+
+    /// `normalize`, additionally dropping insignificant trailing zeros from
+    /// raw numbers when `trim_zeros` is set, so `1.50` becomes `1.5`.
+    pub fn normalize_with(&mut self, trim_zeros: bool) {
+        self.walk_mut(|node| match node {
+            JsonNode::Number(n) if n.as_f64() == 0.0 => *n = Number::new(0.0),
+            JsonNode::RawNumber(text) => *text = normalize_text(text, trim_zeros),
+            JsonNode::Object(map) => map.sort_keys(),
+            _ => {}
+        });
+    }
+
+    /// Turns every number that is exactly an integer in `i64` range into
+    /// a `Number` held as an integer, which serializes without a fraction
+    /// and converts losslessly with `i64::try_from`. So `1.0` and `1e3`
+    /// become `1` and `1000`, while `1.5` and integers beyond `i64` keep
+    /// their current representation, raw or not.
+    pub fn retype_numbers(&mut self) {
+        self.walk_mut(|node| {
+            let n = match node {
+                JsonNode::Number(n) => n.as_i64(),
+                // worked out from the digits, so nothing past 2^53 is rounded
+                JsonNode::RawNumber(text) => {
+                    exact_integer(text).and_then(|n| i64::try_from(n).ok())
+                }
+                _ => None,
+            };
+            if let Some(n) = n {
+                *node = JsonNode::from(n);
+            }
+        });
+    }
+
+    /// Replaces every string value in the tree with `f` applied to it.
+    /// Object keys are left alone; see `map_strings_and_keys`.
+    pub fn map_strings(&mut self, mut f: impl FnMut(&str) -> String) {
+        self.walk_mut(|node| {
+            if let JsonNode::String(s) = node {
+                *s = f(s);
+            }
+        });
+    }
+
+    /// `map_strings`, also rewriting object keys. When two keys of an object
+    /// come out the same, only one of their values is kept.
+    pub fn map_strings_and_keys(&mut self, mut f: impl FnMut(&str) -> String) {
+        self.walk_mut(|node| match node {
+            JsonNode::String(s) => *s = f(s),
+            JsonNode::Object(map) => {
+                let entries = take_entries(map);
+                map.extend(entries.into_iter().map(|(key, value)| (f(&key), value)));
+            }
+            _ => {}
+        });
+    }
+
+    /// Builds a transformed copy of the tree: every scalar is replaced by
+    /// what `f` returns for it, and dropped from its array or object when
+    /// that is `None`. Arrays and objects are kept, even when emptied, so
+    /// `None` only comes back for a scalar root.
+    pub fn filter_map_deep(&self, f: impl Fn(&JsonNode) -> Option<JsonNode>) -> Option<JsonNode> {
+        self.filter_map_deep_with(&f)
+    }
+
+    fn filter_map_deep_with(&self, f: &impl Fn(&JsonNode) -> Option<JsonNode>) -> Option<JsonNode> {
+        match self {
+            JsonNode::Array(vec) => Some(JsonNode::Array(
+                vec.iter()
+                    .filter_map(|v| v.filter_map_deep_with(f))
+                    .collect(),
+            )),
+            JsonNode::Object(map) => {
+                let mut obj = Map::default();
+                for (key, value) in map {
+                    if let Some(value) = value.filter_map_deep_with(f) {
+                        obj.insert(key.clone(), value);
+                    }
+                }
+                Some(JsonNode::Object(obj))
+            }
+            _ => f(self),
+        }
+    }
+
+    /// Calls `f` on this node and then on each of its descendants, depth
+    /// first. A node is visited before its children, so children that `f`
+    /// puts in place are visited too.
+    pub fn walk_mut(&mut self, mut f: impl FnMut(&mut JsonNode)) {
+        self.walk_mut_with(&mut f);
+    }
+
+    fn walk_mut_with(&mut self, f: &mut impl FnMut(&mut JsonNode)) {
+        f(self);
+        match self {
+            JsonNode::Array(vec) => vec.iter_mut().for_each(|v| v.walk_mut_with(f)),
+            JsonNode::Object(map) => map.values_mut().for_each(|v| v.walk_mut_with(f)),
+            _ => {}
+        }
+    }
 }
 
 #[cfg(test)]
@@ -368,6 +1526,10 @@ mod tests {
     static GLOBAL: &StatsAlloc<System> = &INSTRUMENTED_SYSTEM;
 
     use super::*;
+    use crate::{
+        fixtures::GITHUB_EVENT,
+        ser::{to_string, to_string_canonical, to_string_with_options, SerializeOptions},
+    };
 
     #[test]
     fn empty_object() {
@@ -425,8 +1587,980 @@ mod tests {
 
     #[test]
     fn json_object() {
-        let res = parse("{\"id\":\"2489651045\",\"type\":\"CreateEvent\",\"actor\":{\"id\":665991,\"login\":\"petroav\",\"gravatar_id\":\"\",\"url\":\"https://api.github.com/users/petroav\",\"avatar_url\":\"https://avatars.githubusercontent.com/u/665991?\"},\"repo\":{\"id\":28688495,\"name\":\"petroav/6.828\",\"url\":\"https://api.github.com/repos/petroav/6.828\"},\"payload\":{\"ref\":\"master\",\"ref_type\":\"branch\",\"master_branch\":\"master\",\"description\":\"Solution to homework and assignments from MIT's 6.828 (Operating Systems Engineering). Done in my spare time.\",\"pusher_type\":\"user\"},\"public\":true,\"created_at\":\"2015-01-01T15:00:00Z\"}");
+        let res = parse(GITHUB_EVENT);
 
         res.unwrap();
     }
+
+    #[test]
+    fn raw_number() {
+        let options = ParseOptions {
+            raw_numbers: true,
+            ..Default::default()
+        };
+        let json = parse_with_options("[2489651045]", &options).unwrap();
+        let n = &json.as_vec().unwrap()[0];
+
+        assert_eq!(n.as_raw_number().unwrap(), "2489651045");
+        assert_eq!(n.as_f64().unwrap(), 2489651045_f64);
+        assert!(n.as_number().is_none());
+    }
+
+    #[test]
+    fn contains_value() {
+        let json = parse("{\"a\":[1,{\"b\":\"needle\"}],\"c\":null}").unwrap();
+
+        assert!(json.contains_value(&JsonNode::String("needle".to_string())));
+        assert!(!json.contains_value(&JsonNode::from(42_f64)));
+    }
+
+    #[test]
+    fn take() {
+        let mut json = parse("{\"a\":{\"b\":[1,2]}}").unwrap();
+
+        let taken = match &mut json {
+            JsonNode::Object(map) => match map.get_mut("a").unwrap() {
+                JsonNode::Object(inner) => inner.get_mut("b").unwrap().take(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+
+        assert_eq!(taken.as_vec().unwrap().len(), 2);
+        let a = json.as_map().unwrap().get("a").unwrap();
+        assert!(a.as_map().unwrap().get("b").unwrap().is_null());
+    }
+
+    #[test]
+    fn build_containers() {
+        let mut items = JsonNode::array();
+        items.push(JsonNode::from(1_f64));
+        items.push(JsonNode::from(2_f64));
+
+        let mut json = JsonNode::object();
+        assert!(json.insert("items", items).is_none());
+
+        assert_eq!(json, parse("{\"items\":[1,2]}").unwrap());
+        assert!(JsonNode::default().is_null());
+    }
+
+    #[test]
+    fn large_array() {
+        let mut src = String::from("[");
+        for i in 0..100000 {
+            if i > 0 {
+                src.push(',');
+            }
+            src.push_str(&i.to_string());
+        }
+        src.push(']');
+
+        let mut parser = JsonParser::new(&src);
+        let json = parser.parse().unwrap();
+
+        assert_eq!(json.as_vec().unwrap().len(), 100000);
+        assert!(parser.lookahead.is_none());
+    }
+
+    #[test]
+    fn repeated_peek_keeps_one_token() {
+        let mut parser = JsonParser::new("[1,2]");
+        parser.advance();
+
+        assert_eq!(parser.peek().unwrap().index, 1);
+        assert_eq!(parser.peek().unwrap().index, 1);
+        assert_eq!(parser.advance().unwrap().index, 1);
+        assert_eq!(parser.advance().unwrap().index, 2);
+    }
+
+    #[test]
+    fn array_of() {
+        let numbers: Vec<i64> = parse_array_of("[1,2,3]").unwrap();
+        assert_eq!(numbers, vec![1, 2, 3]);
+
+        let err = parse_array_of::<i64>("[1,\"x\"]").unwrap_err();
+        assert_eq!(err.message, "element 1: expected an integer, found string");
+    }
+
+    #[test]
+    fn control_char_outside_string() {
+        let err = parse("[1,\0 2]").unwrap_err();
+
+        assert_eq!(err.message, "unexpected control character");
+        assert_eq!(err.index, Some(3));
+    }
+
+    #[test]
+    fn parse_concatenated_values() {
+        let src = "{\"a\":1} [true]\n\"end\"";
+
+        let (first, offset) = parse_from(src, 0).unwrap();
+        assert_eq!(first, parse("{\"a\":1}").unwrap());
+        assert_eq!(offset, 7);
+
+        let (second, offset) = parse_from(src, offset).unwrap();
+        assert_eq!(second, parse("[true]").unwrap());
+
+        let (third, offset) = parse_from(src, offset).unwrap();
+        assert_eq!(third.as_string().unwrap(), "end");
+        assert_eq!(offset, src.len());
+    }
+
+    #[test]
+    fn as_null() {
+        let json = parse("[null,0]").unwrap();
+        let arr = json.as_vec().unwrap();
+
+        assert_eq!(arr[0].as_null(), Some(()));
+        assert_eq!(arr[1].as_null(), None);
+    }
+
+    #[test]
+    fn missing_object_value() {
+        let err = parse("{\"a\":}").unwrap_err();
+
+        assert_eq!(err.message, "expected a value");
+        assert_eq!(err.index, Some(5));
+    }
+
+    #[test]
+    fn unexpected_close_brace() {
+        let err = parse("}").unwrap_err();
+        assert_eq!(err.message, "Unexpected }");
+    }
+
+    #[test]
+    fn escaped_nul() {
+        let json = parse(r#""\u0000""#).unwrap();
+        let s = json.as_string().unwrap();
+
+        assert_eq!(s.chars().count(), 1);
+        assert_eq!(s.chars().next(), Some('\0'));
+    }
+
+    #[test]
+    fn relaxed() {
+        let src = "{\n  // comment\n  name: 'it\\'s',\n  /* block */ list: [1 2 3,],\n  'quoted': \"x\",\n  null: {a: [true false],},\n}";
+
+        let expected = parse(
+            "{\"name\":\"it's\",\"list\":[1,2,3],\"quoted\":\"x\",\"null\":{\"a\":[true,false]}}",
+        )
+        .unwrap();
+        assert_eq!(parse_relaxed(src).unwrap(), expected);
+        assert!(parse(src).is_err());
+    }
+
+    #[test]
+    fn keyword_prefixed_keys() {
+        let json = parse_relaxed("{nullable: 1, trueValue: 2, falsey: null, null: true}").unwrap();
+        assert_eq!(
+            json,
+            parse(r#"{"nullable": 1, "trueValue": 2, "falsey": null, "null": true}"#).unwrap()
+        );
+
+        let err = parse_relaxed("{a: nullable}").unwrap_err();
+        assert_eq!(err.index, Some(4));
+    }
+
+    #[test]
+    fn strict_rejects_relaxed_syntax() {
+        for src in [
+            "[1,]",
+            "{\"a\":1,}",
+            "[1 2]",
+            "{a:1}",
+            "['a']",
+            "// comment\n[1]",
+        ] {
+            assert!(parse_relaxed(src).is_ok(), "{} should parse relaxed", src);
+            assert!(parse(src).is_err(), "{} should not parse strictly", src);
+        }
+    }
+
+    #[test]
+    fn whitespace_separated_elements() {
+        let parser = ParserBuilder::new().missing_commas(true);
+        assert_eq!(
+            parser.parse("[1 2 3]").unwrap(),
+            parse("[1, 2, 3]").unwrap()
+        );
+        assert_eq!(
+            parser.parse(r#"[[1] {"a": "b"} "c" -4]"#).unwrap(),
+            parse(r#"[[1], {"a": "b"}, "c", -4]"#).unwrap()
+        );
+        // only arrays: object members still need their commas
+        assert!(parser.parse(r#"{"a": 1 "b": 2}"#).is_err());
+
+        let err = parse("[1 2 3]").unwrap_err();
+        assert_eq!(err.message, "expected comma or end of array");
+        assert_eq!(err.index, Some(3));
+    }
+
+    #[test]
+    fn missing_comma_before_non_value() {
+        let err = parse_relaxed("[1 :]").unwrap_err();
+        assert_eq!(err.message, "expected comma or end of array");
+        assert_eq!(err.index, Some(3));
+    }
+
+    #[test]
+    fn max_string_len() {
+        let options = ParseOptions {
+            max_string_len: Some(4),
+            ..Default::default()
+        };
+
+        parse_with_options("{\"abcd\":\"efgh\"}", &options).unwrap();
+
+        let err = parse_with_options("[\"abcd\",\"abcde\"]", &options).unwrap_err();
+        assert_eq!(err.message, "string too long");
+        assert_eq!(err.index, Some(8));
+
+        let err = parse_with_options("{\"abcde\":1}", &options).unwrap_err();
+        assert_eq!(err.message, "string too long");
+        assert_eq!(err.index, Some(1));
+    }
+
+    #[cfg(feature = "normalize-keys")]
+    #[test]
+    fn normalize_keys() {
+        let source = "{\"cafe\u{301}\": 1}";
+        let options = ParseOptions {
+            normalize_keys: Some(NfKind::Nfc),
+            ..Default::default()
+        };
+
+        let json = parse_with_options(source, &options).unwrap();
+        assert_eq!(json.get("caf\u{e9}"), Some(&JsonNode::from(1.0)));
+
+        let json = parse(source).unwrap();
+        assert_eq!(json.get("caf\u{e9}"), None);
+    }
+
+    #[test]
+    fn missing_comma_in_object() {
+        let err = parse(r#"{"a":1 "b":2}"#).unwrap_err();
+        assert_eq!(err.message, "expected comma or object close");
+        assert_eq!(err.index, Some(7));
+        assert_eq!(err.column, Some(8));
+    }
+
+    #[test]
+    fn as_f64_and_integers() {
+        let raw = ParseOptions {
+            raw_numbers: true,
+            ..Default::default()
+        };
+
+        for json in [
+            parse("[42, 4.2, 9007199254740993]").unwrap(),
+            parse_with_options("[42, 4.2, 9007199254740993]", &raw).unwrap(),
+        ] {
+            assert_eq!(json[0].as_f64(), Some(42.0));
+            assert_eq!(json[1].as_f64(), Some(4.2));
+            assert_eq!(json[0].as_i64(), Some(42));
+            assert_eq!(json[0].as_u64(), Some(42));
+            assert_eq!(json[1].as_i64(), None);
+        }
+
+        let json = parse_with_options("[9007199254740993, -1]", &raw).unwrap();
+        assert_eq!(json[0].as_u64(), Some(9007199254740993));
+        assert_eq!(json[1].as_i64(), Some(-1));
+        assert_eq!(json[1].as_u64(), None);
+    }
+
+    #[test]
+    fn hash_comments() {
+        let options = ParseOptions {
+            comments: CommentStyle::Hash,
+            ..Default::default()
+        };
+
+        let json = parse_with_options("{\"a\":1 # note\n}", &options).unwrap();
+        assert_eq!(json["a"], JsonNode::from(1.0));
+        let json = parse_with_options("[\"# kept\"] # trailing", &options).unwrap();
+        assert_eq!(json[0].as_string(), Some(&"# kept".to_string()));
+
+        assert!(parse_with_options("[1 // no\n]", &options).is_err());
+        assert!(parse("{\"a\":1 # note\n}").is_err());
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        let options = ParseOptions {
+            comments: CommentStyle::SlashSlash,
+            ..Default::default()
+        };
+
+        let err = parse_with_options("{\"a\":1 /* oops\n}", &options).unwrap_err();
+        assert_eq!(err.message, "unterminated comment");
+        assert_eq!((err.index, err.line), (Some(7), Some(1)));
+
+        let err = parse_with_options("[ /* oops", &options).unwrap_err();
+        assert_eq!(err.message, "unterminated comment");
+        assert_eq!(err.index, Some(2));
+        assert!(parse_with_options("[1 /* ok */]", &options).is_ok());
+    }
+
+    #[test]
+    fn parser_builder() {
+        let parser = ParserBuilder::new()
+            .comments(CommentStyle::Hash)
+            .unquoted_keys(true)
+            .trailing_commas(true)
+            .raw_numbers(true)
+            .max_string_len(8);
+
+        let json = parser.parse("{id: 1.50, # note\n name: 'x',}");
+        assert!(json.is_err());
+
+        let json = parser.parse("{id: 1.50, # note\n name: \"x\",}").unwrap();
+        assert_eq!(json["id"], JsonNode::RawNumber("1.50".to_string()));
+        assert_eq!(json["name"].as_string(), Some(&"x".to_string()));
+
+        let err = parser.parse(r#"["too long string"]"#).unwrap_err();
+        assert_eq!(err.message, "string too long");
+        assert!(ParserBuilder::new().parse("{id: 1}").is_err());
+    }
+
+    #[test]
+    fn max_elements() {
+        let parser = ParserBuilder::new().max_elements(4);
+
+        // two members plus two elements
+        assert!(parser.parse(r#"{"a": [1, 2], "b": []}"#).is_ok());
+
+        let err = parser.parse(r#"{"a": [1, 2], "b": [3]}"#).unwrap_err();
+        assert_eq!(err.message, "too many elements");
+        assert_eq!(err.index, Some(20));
+
+        let err = parser.parse(r#"[[], [], [], {}, {"x": 1}]"#).unwrap_err();
+        assert_eq!(err.message, "too many elements");
+        assert_eq!(err.index, Some(17));
+    }
+
+    #[test]
+    fn max_depth_boundary() {
+        let parser = ParserBuilder::new().max_depth(3);
+
+        let json = parser.parse("[1, [2, {\"a\": 3}]]").unwrap();
+        assert_eq!(json[1][1]["a"], JsonNode::from(3.0));
+
+        let err = parser.parse("[1, [2, {\"a\": []}]]").unwrap_err();
+        assert_eq!(err.message, "nesting too deep");
+        assert_eq!(err.index, Some(14));
+
+        // depth is restored on the way out, so siblings are not counted as nesting
+        assert!(parser.parse("[[[1]], [[2]], {\"a\": {\"b\": 3}}]").is_ok());
+    }
+
+    #[test]
+    fn accessors_cover_every_representation() {
+        // Written the way downstream code should be: no variant names, so it
+        // keeps working as number representations are added.
+        fn describe(node: &JsonNode) -> String {
+            if let Some(n) = node.as_f64() {
+                format!("number {}", n)
+            } else if let Some(s) = node.as_string() {
+                format!("string {}", s)
+            } else if let Some(vec) = node.as_vec() {
+                format!("array of {}", vec.len())
+            } else {
+                node.type_name().to_string()
+            }
+        }
+
+        let raw = ParseOptions {
+            raw_numbers: true,
+            ..Default::default()
+        };
+        for json in [
+            parse(r#"[1.5, "a", [true], null]"#).unwrap(),
+            parse_with_options(r#"[1.5, "a", [true], null]"#, &raw).unwrap(),
+        ] {
+            let described: Vec<_> = json.as_vec().unwrap().iter().map(describe).collect();
+            assert_eq!(described, ["number 1.5", "string a", "array of 1", "null"]);
+
+            assert!(json[0].is_number());
+            assert!(json[1].is_string());
+            assert!(json[2].is_array() && json[2][0].is_bool());
+            assert!(!json[3].is_object());
+        }
+    }
+
+    #[test]
+    fn top_level_scalar() {
+        assert_eq!(parse("5").unwrap(), JsonNode::from(5.0));
+        assert_eq!(parse("{}").unwrap(), JsonNode::object());
+
+        let strict = ParserBuilder::new().top_level_scalar(false);
+        let err = strict.parse(" 5").unwrap_err();
+        assert_eq!(err.message, "top-level value must be object or array");
+        assert_eq!(err.index, Some(1));
+        assert!(strict.parse("\"s\"").is_err());
+        assert_eq!(strict.parse("{}").unwrap(), JsonNode::object());
+        assert_eq!(strict.parse("[5]").unwrap(), parse("[5]").unwrap());
+    }
+
+    #[test]
+    fn walk_mut() {
+        let mut json = parse(r#"{"name":"a","tags":["b",{"c":"d","n":1}],"ok":true}"#).unwrap();
+        let mut visited = 0;
+
+        json.walk_mut(|node| {
+            visited += 1;
+            if let JsonNode::String(s) = node {
+                *s = s.to_uppercase();
+            }
+        });
+
+        assert_eq!(visited, 8);
+        assert_eq!(
+            json,
+            parse(r#"{"name":"A","tags":["B",{"c":"D","n":1}],"ok":true}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn typed_defaults() {
+        let json = parse(r#"{"name":"x","port":8080,"debug":true}"#).unwrap();
+
+        assert_eq!(json["name"].string_or("none"), "x");
+        assert_eq!(json["port"].number_or(80.0), 8080.0);
+        assert!(json["debug"].bool_or(false));
+
+        assert_eq!(json["port"].string_or("none"), "none");
+        assert_eq!(json["name"].number_or(80.0), 80.0);
+        assert!(!json["name"].bool_or(false));
+        assert_eq!(json["missing"].string_or("none"), "none");
+        assert_eq!(json["missing"].number_or(1.5), 1.5);
+        assert!(json["missing"].bool_or(true));
+    }
+
+    #[test]
+    fn mismatched_brackets() {
+        let err = parse("[1}").unwrap_err();
+        assert_eq!(
+            err.message,
+            "mismatched bracket: expected ']' to close array opened at index 0, found '}'"
+        );
+        assert_eq!(err.index, Some(2));
+
+        let err = parse(r#"{"a":1]"#).unwrap_err();
+        assert_eq!(
+            err.message,
+            "mismatched bracket: expected '}' to close object opened at index 0, found ']'"
+        );
+        assert_eq!(err.index, Some(6));
+
+        let err = parse(r#"{"a": [[], {}}, "b": {]}"#).unwrap_err();
+        assert_eq!(
+            err.message,
+            "mismatched bracket: expected ']' to close array opened at index 6, found '}'"
+        );
+        assert!(parse("[1,}")
+            .unwrap_err()
+            .message
+            .contains("opened at index 0"));
+        assert!(parse("{]")
+            .unwrap_err()
+            .message
+            .contains("opened at index 0"));
+    }
+
+    #[test]
+    fn memory_budget() {
+        let doc = format!("[{}]", vec![GITHUB_EVENT; 50].join(","));
+        let err = ParserBuilder::new()
+            .max_total_bytes(1024)
+            .parse(&doc)
+            .unwrap_err();
+        assert_eq!(err.message, "memory budget exceeded");
+        assert!(err.index.unwrap() < GITHUB_EVENT.len());
+
+        // four nodes, the string "abcd" and the key "key"
+        let source = r#"{"key": ["abcd", 1]}"#;
+        let estimate =
+            4 * core::mem::size_of::<JsonNode>() + 4 + core::mem::size_of::<String>() + 3;
+        assert!(ParserBuilder::new()
+            .max_total_bytes(estimate)
+            .parse(source)
+            .is_ok());
+        let err = ParserBuilder::new()
+            .max_total_bytes(estimate - 1)
+            .parse(source)
+            .unwrap_err();
+        assert_eq!(err.index, source.find('1'));
+    }
+
+    #[test]
+    fn peek_at_eof_is_latched() {
+        let mut parser = JsonParser::new("[1] ");
+        assert!(parser.parse().is_ok());
+
+        for _ in 0..3 {
+            assert!(parser.peek().is_none());
+        }
+        assert!(parser.eof);
+
+        // once latched, the tokenizer is not consulted again
+        parser.tokenizer = Tokenizer::new("2");
+        assert!(parser.peek().is_none());
+        assert!(parser.advance().is_none());
+    }
+
+    #[test]
+    fn f64_array() {
+        let source = "[1.0, -2.5, 3e2, 0]";
+        assert_eq!(
+            parse_f64_array(source).unwrap(),
+            vec![1.0, -2.5, 300.0, 0.0]
+        );
+        assert_eq!(
+            parse_f64_array(source).unwrap(),
+            parse_array_of::<f64>(source).unwrap()
+        );
+        assert_eq!(parse_f64_array(" [ ] ").unwrap(), Vec::<f64>::new());
+
+        let err = parse_f64_array("[1, 2, \"3\"]").unwrap_err();
+        assert_eq!(err.message, "expected a number");
+        assert_eq!(err.index, Some(7));
+
+        assert_eq!(
+            parse_f64_array("[1,]").unwrap_err().message,
+            "expected a number"
+        );
+        assert_eq!(
+            parse_f64_array("[1 2]").unwrap_err().message,
+            "expected comma or end of array"
+        );
+        assert_eq!(
+            parse_f64_array("{}").unwrap_err().message,
+            "expected an array"
+        );
+        assert_eq!(parse_f64_array("[1,").unwrap_err().message, "eof");
+    }
+
+    #[test]
+    fn approx_eq() {
+        let a = parse(r#"{"x":[1.0000001,{"y":2}],"s":"a"}"#).unwrap();
+        let b = parse(r#"{"x":[1.0,{"y":2.0000000001}],"s":"a"}"#).unwrap();
+
+        assert_ne!(a, b);
+        assert!(a.approx_eq(&b, 1e-6));
+        assert!(!a.approx_eq(&b, 1e-9));
+
+        let raw = ParserBuilder::new()
+            .raw_numbers(true)
+            .parse("[1.0]")
+            .unwrap();
+        assert!(raw.approx_eq(&parse("[1.0000001]").unwrap(), 1e-6));
+
+        assert!(!a.approx_eq(&parse(r#"{"x":[1,{"y":2}],"s":"b"}"#).unwrap(), 1e-6));
+        assert!(!parse("[1]")
+            .unwrap()
+            .approx_eq(&parse("[1,1]").unwrap(), 1e-6));
+        assert!(!parse("1")
+            .unwrap()
+            .approx_eq(&parse("\"1\"").unwrap(), 1e-6));
+    }
+
+    #[test]
+    fn invalid_escape_position() {
+        let err = parse(r#"{"a": "x\qy"}"#).unwrap_err();
+        assert_eq!(err.message, "invalid escape");
+        assert_eq!(err.index, Some(8));
+
+        let err = parse(r#"["ok", "\u12zz"]"#).unwrap_err();
+        assert_eq!(err.message, "invalid escape");
+        assert_eq!(err.index, Some(8));
+    }
+
+    #[test]
+    fn normalize() {
+        let raw = ParserBuilder::new().raw_numbers(true);
+        let options = SerializeOptions {
+            sort_keys: true,
+            ..Default::default()
+        };
+        let mut a = raw
+            .parse(r#"{"price":1.50E+02,"ids":[-0,7],"n":{"y":1e-05,"x":null}}"#)
+            .unwrap();
+        let mut b = raw
+            .parse(r#"{"n":{"x":null,"y":1e-5},"ids":[0,7.0],"price":1.5e2}"#)
+            .unwrap();
+        assert_ne!(
+            to_string_with_options(&a, &options),
+            to_string_with_options(&b, &options)
+        );
+
+        a.normalize_with(true);
+        b.normalize_with(true);
+        let text = to_string_with_options(&a, &options).unwrap();
+        assert_eq!(
+            text,
+            r#"{"ids":[0,7],"n":{"x":null,"y":1e-5},"price":1.5e2}"#
+        );
+        assert_eq!(text, to_string_with_options(&b, &options).unwrap());
+        assert_eq!(to_string_canonical(&a), to_string_canonical(&b));
+        assert_eq!(to_string(&a).unwrap(), to_string(&b).unwrap());
+
+        let mut json = parse(r#"{"b":1,"a":{"d":2,"c":3}}"#).unwrap();
+        json.normalize();
+        assert_eq!(to_string(&json).unwrap(), r#"{"a":{"c":3,"d":2},"b":1}"#);
+
+        let mut json = raw.parse("[1.50, -0.0E0]").unwrap();
+        json.normalize();
+        assert_eq!(json, raw.parse("[1.50, 0.0]").unwrap());
+
+        let mut json = JsonNode::from(-0.0);
+        json.normalize();
+        assert_eq!(to_string_with_options(&json, &options).unwrap(), "0");
+    }
+
+    #[test]
+    fn recoverable() {
+        let source = r#"{"a": tru, "b": [1, x, 3,], "c" 4, "d": "\q", "e": {"f": 1,}}"#;
+        let (json, errors) = parse_recoverable(source);
+
+        assert_eq!(
+            json.unwrap(),
+            parse(r#"{"b": [1, 3], "e": {"f": 1}}"#).unwrap()
+        );
+        let found: Vec<(&str, usize)> = errors
+            .iter()
+            .map(|e| (e.message.as_str(), e.index.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("unknown keyword", 6),
+                ("unknown keyword", 20),
+                ("trailing comma in array", 25),
+                ("expect :", 32),
+                ("invalid escape", 41),
+                ("trailing comma in object", 59),
+            ]
+        );
+
+        assert_eq!(
+            parse_recoverable("[1, 2]"),
+            (Some(parse("[1, 2]").unwrap()), vec![])
+        );
+        assert_eq!(
+            parse_recoverable("[1,,2]").0,
+            Some(parse("[1, 2]").unwrap())
+        );
+        assert_eq!(
+            parse_recoverable(r#"{"a": }"#).0,
+            Some(parse("{}").unwrap())
+        );
+
+        let (json, errors) = parse_recoverable("[1, x, {]");
+        assert_eq!(json, None);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[1].message.starts_with("mismatched bracket"));
+        assert_eq!(
+            parse_recoverable("[1, x").1.last().unwrap().message,
+            "unexpected eof"
+        );
+    }
+
+    #[test]
+    fn as_str() {
+        let json = parse(r#"{"name":"x","n":1}"#).unwrap();
+
+        assert!(json["name"].as_str() == Some("x"));
+        assert_eq!(json["n"].as_str(), None);
+    }
+
+    #[test]
+    fn non_finite_spellings() {
+        let allow = ParserBuilder::new().non_finite(NonFinite::Allow);
+        let any_case = ParserBuilder::new().non_finite(NonFinite::AllowAnyCase);
+        let f64_of =
+            |builder: &ParserBuilder, source| builder.parse(source).unwrap().as_f64().unwrap();
+
+        for builder in [&allow, &any_case] {
+            assert_eq!(f64_of(builder, "Infinity"), f64::INFINITY);
+            assert_eq!(f64_of(builder, "+Infinity"), f64::INFINITY);
+            assert_eq!(f64_of(builder, "-Infinity"), f64::NEG_INFINITY);
+            assert!(f64_of(builder, "NaN").is_nan());
+        }
+        assert_eq!(f64_of(&any_case, "infinity"), f64::INFINITY);
+        assert_eq!(f64_of(&any_case, "-INFINITY"), f64::NEG_INFINITY);
+        assert!(f64_of(&any_case, "nan").is_nan());
+        assert_eq!(
+            any_case.parse("[1, -infinity]").unwrap()[1].as_f64(),
+            Some(f64::NEG_INFINITY)
+        );
+
+        assert_eq!(
+            allow.parse("infinity").unwrap_err().message,
+            "unknown keyword"
+        );
+        assert_eq!(
+            allow.parse("Infinite").unwrap_err().message,
+            "unknown keyword"
+        );
+        assert!(parse_relaxed("[NaN, -Infinity]").is_ok());
+
+        for source in ["Infinity", "+Infinity", "-Infinity", "-infinity", "NaN"] {
+            let err = parse(source).unwrap_err();
+            assert_eq!(err.message, "NaN and Infinity are not allowed");
+            assert_eq!(err.index, Some(0));
+        }
+    }
+
+    #[test]
+    fn count_matches() {
+        let json = parse(r#"{"a":true,"b":[true,false,{"c":true,"d":"true"}],"e":null}"#).unwrap();
+
+        assert_eq!(json.count_matches(|node| node.as_bool() == Some(&true)), 3);
+        assert_eq!(
+            json.count_matches(|node| node.is_array() || node.is_object()),
+            3
+        );
+        assert_eq!(json.count_matches(|_| true), 9);
+        assert_eq!(JsonNode::Null.count_matches(|node| node.is_null()), 1);
+    }
+
+    #[test]
+    fn non_string_key() {
+        let err = parse("{1:2}").unwrap_err();
+        assert_eq!(err.message, r#"object key must be a string, found NUM"1""#);
+        assert_eq!(err.index, Some(1));
+
+        let err = parse_relaxed("{[1]: 2}").unwrap_err();
+        assert_eq!(err.message, "object key must be a string, found <[>");
+    }
+
+    #[test]
+    #[cfg(feature = "trace")]
+    fn traced() {
+        let (json, trace) = parse_traced(r#"{"a": [1, "x\n", {"b": null}], "c": true}"#).unwrap();
+        assert_eq!(json["c"], JsonNode::Bool(true));
+        assert_eq!(
+            trace,
+            ParseTrace {
+                tokens: 19,
+                strings_escaped: 4,
+                max_depth: 3,
+            }
+        );
+
+        let mut tokenizer = Tokenizer::new(GITHUB_EVENT);
+        let mut tokens = 0;
+        while tokenizer.next().is_some() {
+            tokens += 1;
+        }
+        let (_, trace) = parse_traced(GITHUB_EVENT).unwrap();
+        assert_eq!(trace.tokens, tokens);
+    }
+
+    #[test]
+    fn lone_surrogate_policies() {
+        let source = r#"["a\ud800b", "\udc00", "\ud800\u0041", "\ud83d\ude00"]"#;
+        let with = |policy| {
+            ParserBuilder::new()
+                .lone_surrogates(policy)
+                .parse(source)
+                .map(|json| json.as_str_array().unwrap().join(","))
+        };
+
+        let err = with(SurrogatePolicy::Error).unwrap_err();
+        assert_eq!(err.message, "lone surrogate");
+        assert_eq!(err.index, Some(1));
+        assert_eq!(
+            with(SurrogatePolicy::ReplacementChar).unwrap(),
+            "a\u{fffd}b,\u{fffd},\u{fffd}A,😀"
+        );
+        assert_eq!(
+            with(SurrogatePolicy::Passthrough).unwrap(),
+            r"a\ud800b,\udc00,\ud800A,😀"
+        );
+    }
+
+    #[test]
+    fn is_truthy() {
+        let json =
+            parse(r#"[false, null, 0, -0.0, "", [], {}, true, 1, -0.5, "0", [0], {"a": null}]"#)
+                .unwrap();
+        let truthy: Vec<bool> = json
+            .as_vec()
+            .unwrap()
+            .iter()
+            .map(JsonNode::is_truthy)
+            .collect();
+
+        assert_eq!(truthy[..7], [false; 7]);
+        assert_eq!(truthy[7..], [true; 6]);
+        assert!(!JsonNode::from(f64::NAN).is_truthy());
+
+        let raw = ParserBuilder::new()
+            .raw_numbers(true)
+            .parse("[0.0, 2]")
+            .unwrap();
+        assert!(!raw[0].is_truthy());
+        assert!(raw[1].is_truthy());
+    }
+
+    #[test]
+    fn nbsp_is_not_whitespace() {
+        let err = parse("[1,\u{a0}2]").unwrap_err();
+        assert_eq!(err.message, "invalid whitespace character");
+        assert_eq!(err.index, Some(3));
+        assert_eq!(err.column, Some(4));
+    }
+
+    #[test]
+    fn spans_of_scalars() {
+        let source = "{\"a/b\": {\"list\": [true, -1.50e+2, \"x\\n\"]},\n \"n\": null}";
+        let (json, spans) = parse_with_spans(source).unwrap();
+        assert_eq!(json, parse(source).unwrap());
+
+        assert_eq!(spans.get("/a~1b/list/1"), Some("-1.50e+2"));
+        assert_eq!(spans.get("/a~1b/list/2"), Some("\"x\\n\""));
+        assert_eq!(spans.get("/a~1b/list/0"), Some("true"));
+        assert_eq!(spans.get("/n"), Some("null"));
+        // containers have no span of their own
+        assert_eq!(spans.get("/a~1b/list"), None);
+        assert_eq!(spans.len(), 4);
+
+        // the spans are slices of the source
+        let text = spans.get("/a~1b/list/1").unwrap();
+        let offset = text.as_ptr() as usize - source.as_ptr() as usize;
+        assert_eq!(&source[offset..offset + text.len()], "-1.50e+2");
+
+        let (_, spans) = parse_with_spans("[[0], 1]").unwrap();
+        let pointers: Vec<_> = spans.iter().map(|(ptr, _)| ptr).collect();
+        assert_eq!(pointers, ["/0/0", "/1"]);
+    }
+
+    #[test]
+    fn try_parse_leaves_rest() {
+        let source = r#"{"a":1}REST"#;
+        let (json, end) = try_parse(source).unwrap();
+        assert_eq!(json, parse(r#"{"a":1}"#).unwrap());
+        assert_eq!(end, 7);
+        assert_eq!(&source[end..], "REST");
+
+        let (_, end) = try_parse(" [1, 2] \n{}").unwrap();
+        assert_eq!(end, 7);
+        assert!(try_parse("{\"a\":").is_err());
+    }
+
+    #[test]
+    fn filter_map_deep() {
+        let json = parse(r#"{"a": 1, "b": "x", "c": [2, "y", {"d": 3, "e": "z"}, [4]]}"#).unwrap();
+        let strings = json
+            .filter_map_deep(|node| node.as_str().map(JsonNode::from))
+            .unwrap();
+        assert_eq!(
+            strings,
+            parse(r#"{"b": "x", "c": ["y", {"e": "z"}, []]}"#).unwrap()
+        );
+        // the original is untouched
+        assert_eq!(json["c"][0], JsonNode::from(2.0));
+
+        let upper = json.filter_map_deep(|node| match node {
+            JsonNode::String(s) => Some(JsonNode::from(s.to_uppercase())),
+            other => Some(other.clone()),
+        });
+        assert_eq!(upper.unwrap()["c"][2]["e"], JsonNode::from("Z"));
+
+        assert_eq!(parse("5").unwrap().filter_map_deep(|_| None), None);
+    }
+
+    #[test]
+    fn map_strings() {
+        let source = r#"{" key ": " a ", "list": ["  b", {"c": "c\t"}, 1]}"#;
+        let mut json = parse(source).unwrap();
+        json.map_strings(|s| s.trim().to_string());
+        assert_eq!(
+            json,
+            parse(r#"{" key ": "a", "list": ["b", {"c": "c"}, 1]}"#).unwrap()
+        );
+
+        let mut json = parse(source).unwrap();
+        json.map_strings_and_keys(|s| s.trim().to_uppercase());
+        assert_eq!(
+            json,
+            parse(r#"{"KEY": "A", "LIST": ["B", {"C": "C"}, 1]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn retype_numbers() {
+        let mut json = parse("[1.0, 1.5, 1e3, -0.0, 1e300]").unwrap();
+        json.retype_numbers();
+        assert_eq!(
+            crate::ser::to_string(&json).unwrap(),
+            "[1,1.5,1000,0,1e300]"
+        );
+        assert_eq!(json[0].as_number().unwrap().as_i64(), Some(1));
+        assert_eq!(json[1].as_number().unwrap().as_i64(), None);
+        assert_eq!(json[2].as_f64(), Some(1000.0));
+
+        let options = ParseOptions {
+            raw_numbers: true,
+            ..Default::default()
+        };
+        let mut json = parse_with_options(
+            r#"{"a": [1.0, 1.5], "b": 1e3, "c": 9007199254740993.0, "d": 1e9223372036854775807}"#,
+            &options,
+        )
+        .unwrap();
+        json.retype_numbers();
+        assert_eq!(json["a"][0], JsonNode::from(1));
+        assert_eq!(json["a"][1], JsonNode::RawNumber("1.5".to_string()));
+        assert_eq!(json["b"].as_f64(), Some(1000.0));
+        assert_eq!(i64::try_from(&json["c"]), Ok(9007199254740993));
+        assert_eq!(
+            json["d"],
+            JsonNode::RawNumber("1e9223372036854775807".to_string())
+        );
+    }
+
+    #[test]
+    fn integers_keep_every_digit() {
+        let json = parse("[9007199254740993, -9007199254740993, 1.5]").unwrap();
+        assert_ne!(json[0], parse("9007199254740992").unwrap());
+        assert_eq!(i64::try_from(&json[1]).unwrap(), -9007199254740993);
+        assert_eq!(
+            crate::ser::to_string(&json).unwrap(),
+            "[9007199254740993,-9007199254740993,1.5]"
+        );
+    }
+
+    #[test]
+    fn number_overflow_lenient() {
+        let json = parse("[1e400,1e-400]").unwrap();
+        let arr = json.as_vec().unwrap();
+
+        assert_eq!(arr[0].as_number().unwrap(), &f64::INFINITY);
+        assert_eq!(arr[1].as_number().unwrap(), &0_f64);
+    }
+
+    #[test]
+    fn number_overflow_error() {
+        let options = ParseOptions {
+            number_overflow: NumberOverflow::Error,
+            ..Default::default()
+        };
+
+        let err = parse_with_options("1e400", &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: number overflow, at index: 0, line: 1"
+        );
+
+        let err = parse_with_options("[1e-400]", &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: number underflow, at index: 1, line: 1"
+        );
+
+        parse_with_options("[0e10,1e300]", &options).unwrap();
+    }
 }
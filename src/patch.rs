@@ -0,0 +1,186 @@
+use crate::{
+    parser::JsonNode,
+    pointer::{array_index, split_last},
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError {
+    pub message: String,
+}
+
+impl PatchError {
+    fn new(message: &str) -> PatchError {
+        PatchError {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "patch error: {}", self.message)
+    }
+}
+
+impl core::error::Error for PatchError {}
+
+/// Applies an RFC 6902 JSON Patch, an array of operation objects, to `doc`.
+///
+/// Operations are applied in order; if one fails, the ones before it stay applied.
+pub fn apply_patch(doc: &mut JsonNode, patch: &JsonNode) -> Result<(), PatchError> {
+    let ops = match patch.as_vec() {
+        Some(ops) => ops,
+        None => return Err(PatchError::new("patch must be an array")),
+    };
+
+    for op in ops {
+        apply_op(doc, op)?;
+    }
+    Ok(())
+}
+
+fn apply_op(doc: &mut JsonNode, op: &JsonNode) -> Result<(), PatchError> {
+    let name = string_member(op, "op")?;
+    let path = string_member(op, "path")?;
+
+    match name {
+        "add" => add(doc, path, value_member(op)?.clone()),
+        "remove" => remove(doc, path).map(|_| ()),
+        "replace" => {
+            let target = match doc.pointer_mut(path) {
+                Some(target) => target,
+                None => return Err(PatchError::new("path does not exist")),
+            };
+            *target = value_member(op)?.clone();
+            Ok(())
+        }
+        "move" => {
+            let from = string_member(op, "from")?;
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(PatchError::new("cannot move a value into itself"));
+            }
+            let value = remove(doc, from)?;
+            add(doc, path, value)
+        }
+        "copy" => {
+            let from = string_member(op, "from")?;
+            let value = match doc.pointer(from) {
+                Some(value) => value.clone(),
+                None => return Err(PatchError::new("from path does not exist")),
+            };
+            add(doc, path, value)
+        }
+        "test" => match doc.pointer(path) {
+            Some(value) if value == value_member(op)? => Ok(()),
+            _ => Err(PatchError::new("test failed")),
+        },
+        _ => Err(PatchError::new("unknown op")),
+    }
+}
+
+fn add(doc: &mut JsonNode, path: &str, value: JsonNode) -> Result<(), PatchError> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+
+    let (parent, last) = match split_last(path) {
+        Some(split) => split,
+        None => return Err(PatchError::new("invalid path")),
+    };
+
+    match doc.pointer_mut(parent) {
+        Some(JsonNode::Object(map)) => {
+            map.insert(last, value);
+            Ok(())
+        }
+        // `-` appends, per RFC 6901 section 4
+        Some(JsonNode::Array(vec)) if last == "-" => {
+            vec.push(value);
+            Ok(())
+        }
+        Some(JsonNode::Array(vec)) => match array_index(&last) {
+            Some(i) if i <= vec.len() => {
+                vec.insert(i, value);
+                Ok(())
+            }
+            _ => Err(PatchError::new("array index out of bounds")),
+        },
+        Some(_) => Err(PatchError::new("parent is not a container")),
+        None => Err(PatchError::new("path does not exist")),
+    }
+}
+
+fn remove(doc: &mut JsonNode, path: &str) -> Result<JsonNode, PatchError> {
+    if path.is_empty() {
+        return Err(PatchError::new("cannot remove the whole document"));
+    }
+    doc.remove_path(path)
+        .ok_or_else(|| PatchError::new("path does not exist"))
+}
+
+fn string_member<'a>(op: &'a JsonNode, key: &str) -> Result<&'a str, PatchError> {
+    match op.get(key).and_then(|v| v.as_string()) {
+        Some(s) => Ok(s),
+        None => Err(PatchError {
+            message: format!("operation is missing \"{}\"", key),
+        }),
+    }
+}
+
+fn value_member(op: &JsonNode) -> Result<&JsonNode, PatchError> {
+    op.get("value")
+        .ok_or_else(|| PatchError::new("operation is missing \"value\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn add_appends_with_dash() {
+        let mut doc = parse(r#"{"arr":[1,2]}"#).unwrap();
+        let patch = parse(r#"[{"op":"add","path":"/arr/-","value":3}]"#).unwrap();
+
+        apply_patch(&mut doc, &patch).unwrap();
+        assert_eq!(doc, parse(r#"{"arr":[1,2,3]}"#).unwrap());
+        assert!(doc.pointer("/arr/-").is_none());
+    }
+
+    #[test]
+    fn operations() {
+        let mut doc = parse(r#"{"a":[1,2],"b":{"c":"d"}}"#).unwrap();
+        let patch = parse(
+            r#"[
+                {"op":"add","path":"/a/0","value":0},
+                {"op":"remove","path":"/a/2"},
+                {"op":"replace","path":"/b/c","value":"e"},
+                {"op":"copy","from":"/b","path":"/f"},
+                {"op":"move","from":"/a","path":"/b/a"},
+                {"op":"test","path":"/f/c","value":"e"}
+            ]"#,
+        )
+        .unwrap();
+
+        apply_patch(&mut doc, &patch).unwrap();
+        assert_eq!(
+            doc,
+            parse(r#"{"b":{"c":"e","a":[0,1]},"f":{"c":"e"}}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn failed_test_op() {
+        let mut doc = parse(r#"{"a":1}"#).unwrap();
+        let patch = parse(r#"[{"op":"test","path":"/a","value":2}]"#).unwrap();
+
+        let err = apply_patch(&mut doc, &patch).unwrap_err();
+        assert_eq!(err.message, "test failed");
+    }
+}
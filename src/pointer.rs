@@ -0,0 +1,212 @@
+use crate::{index::NULL, parser::JsonNode};
+use alloc::{format, string::String, vec, vec::Vec};
+
+impl JsonNode {
+    /// Looks up a value by RFC 6901 JSON Pointer, e.g. `/repo/name` or `/items/0`.
+    ///
+    /// The `-` array token names the position after the last element, which
+    /// holds no value, so it always resolves to `None` here.
+    pub fn pointer(&self, ptr: &str) -> Option<&JsonNode> {
+        let mut node = self;
+        for token in tokens(ptr)? {
+            node = match node {
+                JsonNode::Object(map) => map.get(&token)?,
+                JsonNode::Array(vec) => vec.get(array_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(node)
+    }
+
+    pub fn pointer_mut(&mut self, ptr: &str) -> Option<&mut JsonNode> {
+        let mut node = self;
+        for token in tokens(ptr)? {
+            node = match node {
+                JsonNode::Object(map) => map.get_mut(&token)?,
+                JsonNode::Array(vec) => vec.get_mut(array_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(node)
+    }
+
+    /// `pointer`, falling back to `default` when nothing is at `ptr`.
+    pub fn pointer_or<'a>(&'a self, ptr: &str, default: &'a JsonNode) -> &'a JsonNode {
+        self.pointer(ptr).unwrap_or(default)
+    }
+
+    /// `pointer`, falling back to `null` when nothing is at `ptr`, the way
+    /// indexing with a missing key does.
+    pub fn pointer_or_default(&self, ptr: &str) -> &JsonNode {
+        self.pointer_or(ptr, &NULL)
+    }
+
+    /// The JSON Pointer of every node in the tree, containers included,
+    /// starting with `""` for the root. Children follow their parent, with
+    /// object members in key order.
+    pub fn ptr_all(&self) -> Vec<String> {
+        let mut pointers = vec![];
+        let mut stack = vec![(String::new(), self)];
+        while let Some((ptr, node)) = stack.pop() {
+            // pushed in reverse so they pop in order
+            match node {
+                JsonNode::Array(vec) => {
+                    for (i, item) in vec.iter().enumerate().rev() {
+                        stack.push((format!("{}/{}", ptr, i), item));
+                    }
+                }
+                JsonNode::Object(map) => {
+                    let mut entries: Vec<_> = map.iter().collect();
+                    entries.sort_by(|a, b| b.0.cmp(a.0));
+                    for (key, value) in entries {
+                        stack.push((format!("{}/{}", ptr, escape_token(key)), value));
+                    }
+                }
+                _ => {}
+            }
+            pointers.push(ptr);
+        }
+        pointers
+    }
+
+    pub fn path_exists(&self, ptr: &str) -> bool {
+        self.pointer(ptr).is_some()
+    }
+
+    /// Removes and returns the value at `ptr`. Removing an array element
+    /// shifts the ones after it down. The whole document (`""`) cannot be
+    /// removed.
+    pub fn remove_path(&mut self, ptr: &str) -> Option<JsonNode> {
+        let (parent, last) = split_last(ptr)?;
+        match self.pointer_mut(parent)? {
+            JsonNode::Object(map) => map.shift_remove(&last),
+            JsonNode::Array(vec) => match array_index(&last) {
+                Some(i) if i < vec.len() => Some(vec.remove(i)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Escapes `~` and `/` in `token` so it can be appended to a pointer.
+pub(crate) fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Splits a pointer into its unescaped reference tokens, or `None` if it is
+/// neither empty nor starts with `/`.
+pub(crate) fn tokens(ptr: &str) -> Option<Vec<String>> {
+    if ptr.is_empty() {
+        return Some(vec![]);
+    }
+
+    let rest = ptr.strip_prefix('/')?;
+    Some(
+        rest.split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Splits a pointer into the pointer to its parent and its last unescaped token.
+pub(crate) fn split_last(ptr: &str) -> Option<(&str, String)> {
+    let slash = ptr.rfind('/')?;
+    let last = ptr[slash + 1..].replace("~1", "/").replace("~0", "~");
+    Some((&ptr[..slash], last))
+}
+
+/// Parses an array index token: decimal digits without leading zeros.
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+
+    #[test]
+    fn pointer() {
+        let json = parse(r#"{"a/b":{"m~n":[10,20]},"":1}"#).unwrap();
+
+        assert_eq!(json.pointer(""), Some(&json));
+        assert_eq!(json.pointer("/").unwrap().as_f64(), Some(1_f64));
+        assert_eq!(json.pointer("/a~1b/m~0n/1").unwrap().as_f64(), Some(20_f64));
+        assert!(json.pointer("/a~1b/m~0n/2").is_none());
+        assert!(json.pointer("/a~1b/m~0n/01").is_none());
+        assert!(json.pointer("/a~1b/m~0n/-").is_none());
+        assert!(json.pointer("a").is_none());
+    }
+
+    #[test]
+    fn pointer_mut() {
+        let mut json = parse(r#"{"a":[1,2]}"#).unwrap();
+        json.pointer_mut("/a/0").unwrap().take();
+
+        assert_eq!(json, parse(r#"{"a":[null,2]}"#).unwrap());
+    }
+
+    #[test]
+    fn pointer_or() {
+        let json = parse(r#"{"server":{"port":8080}}"#).unwrap();
+        let fallback = parse("80").unwrap();
+
+        assert_eq!(
+            json.pointer_or("/server/port", &fallback).as_f64(),
+            Some(8080.0)
+        );
+        assert_eq!(json.pointer_or("/server/host", &fallback), &fallback);
+        assert!(json.pointer_or_default("/server/host").is_null());
+    }
+
+    #[test]
+    fn ptr_all() {
+        let json = parse(r#"{"repo":{"name":"x","tags":["a",{}]},"a/b":null}"#).unwrap();
+        assert_eq!(
+            json.ptr_all(),
+            [
+                "",
+                "/a~1b",
+                "/repo",
+                "/repo/name",
+                "/repo/tags",
+                "/repo/tags/0",
+                "/repo/tags/1"
+            ]
+        );
+
+        for ptr in json.ptr_all() {
+            assert!(json.path_exists(&ptr), "{}", ptr);
+        }
+        assert_eq!(parse("1").unwrap().ptr_all(), [""]);
+    }
+
+    #[test]
+    fn path_exists() {
+        let json = parse(r#"{"a":{"b":null},"c":[1]}"#).unwrap();
+
+        assert!(json.path_exists("/a/b"));
+        assert!(json.path_exists("/c/0"));
+        assert!(!json.path_exists("/c/1"));
+        assert!(!json.path_exists("/a/x"));
+    }
+
+    #[test]
+    fn remove_path() {
+        let mut json = parse(r#"{"a":{"b":1,"c":2},"list":[10,20,30]}"#).unwrap();
+
+        assert_eq!(json.remove_path("/a/b"), Some(parse("1").unwrap()));
+        assert_eq!(json.remove_path("/list/0"), Some(parse("10").unwrap()));
+        assert_eq!(json, parse(r#"{"a":{"c":2},"list":[20,30]}"#).unwrap());
+
+        assert_eq!(json.remove_path("/list/2"), None);
+        assert_eq!(json.remove_path("/a/b"), None);
+        assert_eq!(json.remove_path(""), None);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::parser::JsonNode;
+
+/// Optional navigation without `and_then` chains: every step on a missing
+/// node is a no-op, and the terminal methods return `None`.
+///
+/// ```
+/// use json_parser::parser::parse;
+///
+/// let json = parse(r#"{"repo":{"tags":["a","b"]}}"#).unwrap();
+/// assert_eq!(json.query().key("repo").key("tags").index(1).as_str(), Some("b"));
+/// assert_eq!(json.query().key("owner").key("name").as_str(), None);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Query<'a>(pub Option<&'a JsonNode>);
+
+impl JsonNode {
+    pub fn query(&self) -> Query<'_> {
+        Query(Some(self))
+    }
+}
+
+impl<'a> Query<'a> {
+    /// Steps into member `key` of an object.
+    pub fn key(self, key: &str) -> Query<'a> {
+        Query(self.0.and_then(|node| node.get(key)))
+    }
+
+    /// Steps into element `i` of an array.
+    pub fn index(self, i: usize) -> Query<'a> {
+        Query(self.0.and_then(|node| node.get_index(i)))
+    }
+
+    pub fn get(self) -> Option<&'a JsonNode> {
+        self.0
+    }
+
+    pub fn as_str(self) -> Option<&'a str> {
+        self.0?.as_str()
+    }
+
+    pub fn as_f64(self) -> Option<f64> {
+        self.0?.as_f64()
+    }
+
+    pub fn as_bool(self) -> Option<bool> {
+        self.0?.as_bool().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fixtures::GITHUB_EVENT, parser::parse};
+
+    #[test]
+    fn query_fixture() {
+        let json = parse(GITHUB_EVENT).unwrap();
+
+        assert_eq!(
+            json.query().key("repo").key("name").as_str(),
+            Some("petroav/6.828")
+        );
+        assert_eq!(json.query().key("actor").key("id").as_f64(), Some(665991.0));
+        assert_eq!(json.query().key("public").as_bool(), Some(true));
+        assert_eq!(json.query().key("repo").get(), json.get("repo"));
+
+        assert_eq!(json.query().key("repo").key("missing").as_str(), None);
+        assert_eq!(json.query().key("type").key("nested").as_str(), None);
+        assert_eq!(json.query().index(0).get(), None);
+    }
+}
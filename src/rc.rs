@@ -0,0 +1,201 @@
+use crate::{
+    error::ParseError,
+    parser::{parse_with_options, JsonNode, ParseOptions},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+/// A `JsonNode` whose strings and containers live behind `Rc`, so cloning a
+/// subtree to hand it to another consumer or parent is a reference-count bump
+/// instead of a deep copy.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonNodeRc {
+    String(Rc<str>),
+    Number(f64),
+    RawNumber(Rc<str>),
+    Array(Rc<Vec<JsonNodeRc>>),
+    Object(Rc<HashMap<String, JsonNodeRc>>),
+    Bool(bool),
+    Null,
+}
+
+impl JsonNodeRc {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonNodeRc::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            JsonNodeRc::Number(n) => Some(*n),
+            JsonNodeRc::RawNumber(s) => s.parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonNodeRc::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_vec(&self) -> Option<&Vec<JsonNodeRc>> {
+        match self {
+            JsonNodeRc::Array(vec) => Some(vec),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&HashMap<String, JsonNodeRc>> {
+        match self {
+            JsonNodeRc::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonNodeRc::Null)
+    }
+
+    /// Deep-copies this tree back into an owned `JsonNode`.
+    pub fn to_node(&self) -> JsonNode {
+        match self {
+            JsonNodeRc::String(s) => JsonNode::String(s.to_string()),
+            JsonNodeRc::Number(n) => JsonNode::from(*n),
+            JsonNodeRc::RawNumber(s) => JsonNode::RawNumber(s.to_string()),
+            JsonNodeRc::Array(vec) => JsonNode::Array(vec.iter().map(|v| v.to_node()).collect()),
+            JsonNodeRc::Object(map) => {
+                JsonNode::Object(map.iter().map(|(k, v)| (k.clone(), v.to_node())).collect())
+            }
+            JsonNodeRc::Bool(b) => JsonNode::Bool(*b),
+            JsonNodeRc::Null => JsonNode::Null,
+        }
+    }
+}
+
+/// Parses `source` into a `JsonNodeRc`. With `options.intern_strings`, equal
+/// string values share one `Rc<str>`. The document is read into a
+/// `JsonNode` first, so interning lowers what the tree keeps, not the peak.
+pub fn parse_rc(source: &str, options: &ParseOptions) -> Result<JsonNodeRc, ParseError> {
+    let json = parse_with_options(source, options)?;
+    if !options.intern_strings {
+        return Ok(JsonNodeRc::from(json));
+    }
+
+    let mut interned: HashSet<Rc<str>> = HashSet::new();
+    Ok(JsonNodeRc::convert(json, &mut |s| {
+        if let Some(shared) = interned.get(s.as_str()) {
+            return shared.clone();
+        }
+        let shared: Rc<str> = s.into();
+        interned.insert(shared.clone());
+        shared
+    }))
+}
+
+impl JsonNodeRc {
+    /// Converts `node`, turning each string value into an `Rc<str>` with `string`.
+    fn convert(node: JsonNode, string: &mut impl FnMut(String) -> Rc<str>) -> JsonNodeRc {
+        match node {
+            JsonNode::String(s) => JsonNodeRc::String(string(s)),
+            JsonNode::Number(n) => JsonNodeRc::Number(n.as_f64()),
+            JsonNode::RawNumber(s) => JsonNodeRc::RawNumber(s.into()),
+            JsonNode::Array(vec) => JsonNodeRc::Array(Rc::new(
+                vec.into_iter()
+                    .map(|v| JsonNodeRc::convert(v, string))
+                    .collect(),
+            )),
+            JsonNode::Object(map) => JsonNodeRc::Object(Rc::new(
+                map.into_iter()
+                    .map(|(k, v)| (k, JsonNodeRc::convert(v, string)))
+                    .collect(),
+            )),
+            JsonNode::Bool(b) => JsonNodeRc::Bool(b),
+            JsonNode::Null => JsonNodeRc::Null,
+        }
+    }
+}
+
+impl From<JsonNode> for JsonNodeRc {
+    fn from(node: JsonNode) -> Self {
+        JsonNodeRc::convert(node, &mut |s| s.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::GITHUB_EVENT, parser::parse};
+
+    #[test]
+    fn shared_subtree() {
+        let event = JsonNodeRc::from(parse(GITHUB_EVENT).unwrap());
+        let repo = event.as_map().unwrap().get("repo").unwrap();
+
+        let first =
+            JsonNodeRc::Object(Rc::new(HashMap::from([("repo".to_string(), repo.clone())])));
+        let second = JsonNodeRc::Object(Rc::new(HashMap::from([(
+            "origin".to_string(),
+            repo.clone(),
+        )])));
+
+        let inner = match repo {
+            JsonNodeRc::Object(map) => map,
+            _ => unreachable!(),
+        };
+        assert_eq!(Rc::strong_count(inner), 3);
+
+        let a = first.as_map().unwrap().get("repo").unwrap();
+        let b = second.as_map().unwrap().get("origin").unwrap();
+        match (a, b) {
+            (JsonNodeRc::Object(a), JsonNodeRc::Object(b)) => assert!(Rc::ptr_eq(a, b)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn interned_strings_are_shared() {
+        let source = r#"[{"level": "warn"}, {"level": "warn"}, "warn", "info"]"#;
+        let options = ParseOptions {
+            intern_strings: true,
+            ..Default::default()
+        };
+        let json = parse_rc(source, &options).unwrap();
+        assert_eq!(json.to_node(), parse(source).unwrap());
+
+        let vec = json.as_vec().unwrap();
+        let level = |i: usize| match vec[i].as_map().unwrap().get("level") {
+            Some(JsonNodeRc::String(s)) => s.clone(),
+            _ => unreachable!(),
+        };
+        let string = |i: usize| match &vec[i] {
+            JsonNodeRc::String(s) => s.clone(),
+            _ => unreachable!(),
+        };
+        assert!(Rc::ptr_eq(&level(0), &level(1)));
+        assert!(Rc::ptr_eq(&level(0), &string(2)));
+        assert!(!Rc::ptr_eq(&string(2), &string(3)));
+
+        // without the option every string has its own allocation
+        let json = parse_rc(source, &ParseOptions::default()).unwrap();
+        let vec = json.as_vec().unwrap();
+        match (
+            &vec[2],
+            json.as_vec().unwrap()[0].as_map().unwrap().get("level"),
+        ) {
+            (JsonNodeRc::String(a), Some(JsonNodeRc::String(b))) => assert!(!Rc::ptr_eq(a, b)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let json = parse(GITHUB_EVENT).unwrap();
+        assert_eq!(JsonNodeRc::from(json.clone()).to_node(), json);
+    }
+}
@@ -0,0 +1,421 @@
+use std::io::{ErrorKind, Read};
+
+use crate::{
+    error::{ParseError, ParseErrorKind},
+    parser::{parse, parse_from, JsonNode},
+};
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Reads a whole document from `reader` and parses it. The input is decoded
+/// as UTF-8 chunk by chunk, so a character split across two reads is fine.
+pub fn parse_reader<R: Read>(mut reader: R) -> Result<JsonNode, ParseError> {
+    let mut decoder = Utf8Decoder::default();
+    let mut buf = [0; CHUNK_SIZE];
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(ParseError::new(&format!("io error: {}", e))),
+        };
+        decoder.push(&buf[..n])?;
+    }
+
+    parse(&decoder.finish()?)
+}
+
+/// Yields the elements of an array read from `reader` one at a time, so a
+/// large `[elem, elem, ...]` never has to be held in memory as a whole.
+///
+/// Only the bytes of the element being parsed are buffered. Iteration stops
+/// after the closing `]` or after the first error, which includes input that
+/// does not start with an array.
+pub struct ArrayStream<R> {
+    reader: R,
+    buf: Vec<u8>,
+    eof: bool,
+    first: bool,
+    done: bool,
+    // bytes and newlines already drained from `buf`, for error positions
+    offset: usize,
+    line: usize,
+    // an error `new` found before the first element, yielded first
+    pending: Option<ParseError>,
+}
+
+impl<R: Read> ArrayStream<R> {
+    /// Streams the array `reader` starts with, opening `[` included. Input
+    /// that is empty, starts with anything else or cannot be read yields its
+    /// error as the first item.
+    pub fn new(reader: R) -> Self {
+        let mut stream = Self::inside(reader);
+        match stream.skip_whitespace() {
+            Ok(Some(b'[')) => stream.consume(1),
+            Ok(Some(_)) => stream.pending = Some(stream.error("expected an array", 0)),
+            Ok(None) => stream.pending = Some(ParseError::empty()),
+            Err(e) => stream.pending = Some(e),
+        }
+        stream
+    }
+
+    /// Streams the rest of an array whose opening `[` has already been
+    /// consumed from `reader`.
+    pub fn inside(reader: R) -> Self {
+        ArrayStream {
+            reader,
+            buf: Vec::new(),
+            eof: false,
+            first: true,
+            done: false,
+            offset: 0,
+            line: 1,
+            pending: None,
+        }
+    }
+
+    /// Reads another chunk into `buf`, returning false at the end of input.
+    fn fill(&mut self) -> Result<bool, ParseError> {
+        if self.eof {
+            return Ok(false);
+        }
+        let mut chunk = [0; CHUNK_SIZE];
+        loop {
+            match self.reader.read(&mut chunk) {
+                Ok(0) => {
+                    self.eof = true;
+                    return Ok(false);
+                }
+                Ok(n) => {
+                    self.buf.extend_from_slice(&chunk[..n]);
+                    return Ok(true);
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(ParseError::new(&format!("io error: {}", e))),
+            }
+        }
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.offset += n;
+        self.line += self.buf[..n].iter().filter(|&&b| b == b'\n').count();
+        self.buf.drain(..n);
+    }
+
+    /// Drops leading whitespace and returns the next byte, if any.
+    fn skip_whitespace(&mut self) -> Result<Option<u8>, ParseError> {
+        loop {
+            let n = self
+                .buf
+                .iter()
+                .take_while(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+                .count();
+            self.consume(n);
+            if let Some(&b) = self.buf.first() {
+                return Ok(Some(b));
+            }
+            if !self.fill()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Finds the `,` or `]` that ends the element at the start of `buf`,
+    /// reading more input as needed.
+    fn element_end(&mut self) -> Result<Option<usize>, ParseError> {
+        let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+        let mut i = 0;
+        loop {
+            while i < self.buf.len() {
+                let b = self.buf[i];
+                if in_string {
+                    match b {
+                        _ if escaped => escaped = false,
+                        b'\\' => escaped = true,
+                        b'"' => in_string = false,
+                        _ => {}
+                    }
+                } else {
+                    match b {
+                        b'"' => in_string = true,
+                        b'[' | b'{' => depth += 1,
+                        b',' | b']' if depth == 0 => return Ok(Some(i)),
+                        b']' | b'}' => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+                }
+                i += 1;
+            }
+            if !self.fill()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn error(&self, message: &str, index: usize) -> ParseError {
+        ParseError {
+            index: Some(self.offset + index),
+            line: Some(self.line),
+            ..ParseError::new(message)
+        }
+    }
+
+    fn eof(&self) -> ParseError {
+        ParseError {
+            kind: ParseErrorKind::UnexpectedEof,
+            ..self.error("unexpected eof", self.buf.len())
+        }
+    }
+
+    fn step(&mut self) -> Result<Option<JsonNode>, ParseError> {
+        match self.skip_whitespace()? {
+            None => return Err(self.eof()),
+            Some(b']') if self.first => {
+                self.consume(1);
+                return Ok(None);
+            }
+            Some(b']') => return Err(self.error("trailing comma in array", 0)),
+            _ => {}
+        }
+        self.first = false;
+
+        let end = match self.element_end()? {
+            Some(end) => end,
+            None => return Err(self.eof()),
+        };
+        let text = std::str::from_utf8(&self.buf[..end])
+            .map_err(|e| self.error("invalid utf-8", e.valid_up_to()))?;
+
+        let result = match parse_from(text, 0) {
+            Ok((json, end)) if text[end..].trim().is_empty() => Ok(json),
+            Ok((_, end)) => {
+                let index = end + (text[end..].len() - text[end..].trim_start().len());
+                let line = text[..index].matches('\n').count() + 1;
+                Err(ParseError::at(
+                    "unexpected text after value",
+                    text,
+                    index,
+                    line,
+                ))
+            }
+            Err(e) => Err(e),
+        };
+
+        // positions are relative to the element; make them relative to the input
+        let json = result.map_err(|e| ParseError {
+            index: e.index.map(|i| self.offset + i),
+            line: e.line.map(|l| self.line + l - 1),
+            ..e
+        })?;
+
+        self.done = self.buf[end] == b']';
+        self.consume(end + 1);
+        Ok(Some(json))
+    }
+}
+
+impl<R: Read> Iterator for ArrayStream<R> {
+    type Item = Result<JsonNode, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if let Some(e) = self.pending.take() {
+            self.done = true;
+            return Some(Err(e));
+        }
+        match self.step() {
+            Ok(Some(json)) => Some(Ok(json)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Decodes UTF-8 incrementally, holding back the bytes of a character that
+/// the current chunk cuts off until the next chunk completes it.
+#[derive(Default)]
+struct Utf8Decoder {
+    text: String,
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    fn push(&mut self, chunk: &[u8]) -> Result<(), ParseError> {
+        self.pending.extend_from_slice(chunk);
+
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(s) => s.len(),
+            // error_len() is None when the input just ends mid-character
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(self.invalid(e.valid_up_to())),
+        };
+
+        // from_utf8 has validated pending[..valid]
+        self.text
+            .push_str(std::str::from_utf8(&self.pending[..valid]).unwrap());
+        self.pending.drain(..valid);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<String, ParseError> {
+        if !self.pending.is_empty() {
+            return Err(self.invalid(0));
+        }
+        Ok(self.text)
+    }
+
+    fn invalid(&self, offset: usize) -> ParseError {
+        let index = self.text.len() + offset;
+        let line = self.text.matches('\n').count() + 1;
+        ParseError {
+            index: Some(index),
+            line: Some(line),
+            ..ParseError::new("invalid utf-8")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::GITHUB_EVENT;
+
+    /// Hands out one byte per `read` call.
+    struct ByteReader<'a>(&'a [u8]);
+
+    impl Read for ByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.split_first() {
+                Some((&b, rest)) if !buf.is_empty() => {
+                    buf[0] = b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn one_byte_at_a_time() {
+        let source = r#"{"name":"café 😀","tags":["ü"]}"#;
+        let json = parse_reader(ByteReader(source.as_bytes())).unwrap();
+
+        assert_eq!(json, parse(source).unwrap());
+        assert_eq!(json["name"].as_string(), Some(&"café 😀".to_string()));
+    }
+
+    #[test]
+    fn whole_fixture() {
+        let json = parse_reader(GITHUB_EVENT.as_bytes()).unwrap();
+        assert_eq!(json, parse(GITHUB_EVENT).unwrap());
+    }
+
+    #[test]
+    fn array_stream_yields_elements() {
+        let mut stream = ArrayStream::new(ByteReader(b"[1,2,3]"));
+        assert_eq!(stream.next(), Some(parse("1")));
+        assert_eq!(stream.next(), Some(parse("2")));
+        assert_eq!(stream.next(), Some(parse("3")));
+        assert_eq!(stream.next(), None);
+    }
+
+    #[test]
+    fn array_stream_nested_elements() {
+        let source = r#" [ {"a": [1, "],"]}, [[]] , "x\"]" ] trailing"#;
+        let items: Vec<_> = ArrayStream::new(ByteReader(source.as_bytes()))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0], parse(r#"{"a": [1, "],"]}"#).unwrap());
+        assert_eq!(items[1], parse("[[]]").unwrap());
+        assert_eq!(items[2].as_str(), Some("x\"]"));
+
+        // already past the opening bracket
+        let mut stream = ArrayStream::inside(&b"[1], 2]"[..]);
+        assert_eq!(stream.next(), Some(parse("[1]")));
+        assert_eq!(stream.next(), Some(parse("2")));
+        assert_eq!(stream.next(), None);
+
+        assert_eq!(ArrayStream::new(&b"[ ]"[..]).count(), 0);
+    }
+
+    #[test]
+    fn array_stream_stops_after_error() {
+        let mut stream = ArrayStream::new(&b"[1, tru, 3]"[..]);
+        assert_eq!(stream.next(), Some(parse("1")));
+        let err = stream.next().unwrap().unwrap_err();
+        assert_eq!(err.index, Some(4));
+        assert_eq!(stream.next(), None);
+
+        let mut stream = ArrayStream::new(&b"[1,]"[..]);
+        stream.next();
+        assert_eq!(
+            stream.next().unwrap().unwrap_err().message,
+            "trailing comma in array"
+        );
+
+        let mut stream = ArrayStream::new(&b"[1, 2"[..]);
+        stream.next();
+        assert_eq!(
+            stream.next().unwrap().unwrap_err().message,
+            "unexpected eof"
+        );
+
+        let mut stream = ArrayStream::new(&b"[1 2]"[..]);
+        let err = stream.next().unwrap().unwrap_err();
+        assert_eq!(err.message, "unexpected text after value");
+        assert_eq!(err.index, Some(3));
+        assert_eq!(stream.next(), None);
+
+        let mut stream = ArrayStream::new(&b"[0, {\"a\":1} x, 3]"[..]);
+        assert_eq!(stream.next(), Some(parse("0")));
+        assert_eq!(stream.next().unwrap().unwrap_err().index, Some(12));
+        assert_eq!(stream.next(), None);
+
+        let mut stream = ArrayStream::new(&b"\n {}"[..]);
+        let err = stream.next().unwrap().unwrap_err();
+        assert_eq!(err.message, "expected an array");
+        assert_eq!((err.index, err.line), (Some(2), Some(2)));
+        assert_eq!(stream.next(), None);
+
+        let err = ArrayStream::new(&b"  "[..]).next().unwrap().unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::EmptyDocument);
+    }
+
+    #[test]
+    fn array_stream_io_error() {
+        struct Broken;
+
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk on fire"))
+            }
+        }
+
+        let mut stream = ArrayStream::new(Broken);
+        let err = stream.next().unwrap().unwrap_err();
+        assert_eq!(err.message, "io error: disk on fire");
+        assert_eq!(stream.next(), None);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let err = parse_reader(ByteReader(b"[\"a\xff\"]")).unwrap_err();
+        assert_eq!(err.message, "invalid utf-8");
+        assert_eq!(err.index, Some(3));
+
+        let err = parse_reader(ByteReader(b"[\"\xc3")).unwrap_err();
+        assert_eq!(err.message, "invalid utf-8");
+        assert_eq!(err.index, Some(2));
+    }
+}
@@ -0,0 +1,636 @@
+use crate::{
+    escape::{escape_into, escaped_len},
+    number::{is_integral, parse_number, Number},
+    parser::JsonNode,
+};
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    io::Write,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    /// Write NaN and the infinities as the bare words `NaN`, `Infinity` and
+    /// `-Infinity` (as JSON5 and JavaScript do) instead of failing. The output
+    /// is then not valid JSON.
+    pub allow_non_finite: bool,
+    /// Escape every non-ASCII character as `\uXXXX`, so the output is plain ASCII.
+    pub ascii_only: bool,
+    /// Put each array element and object member on its own line, indented by
+    /// this many spaces per level. `None` writes compact output.
+    pub indent: Option<usize>,
+    /// Write object members in key order instead of map order.
+    pub sort_keys: bool,
+    /// Escape U+2028 and U+2029 as `\u2028` and `\u2029`. Both are valid in
+    /// JSON strings but end a line in JavaScript source, so JSON embedded in
+    /// a `<script>` needs them escaped.
+    pub escape_js_separators: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializeError {
+    pub message: String,
+}
+
+impl Display for SerializeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "serialize error: {}", self.message)
+    }
+}
+
+impl core::error::Error for SerializeError {}
+
+/// Serializes `node` as compact JSON text. Fails on NaN and infinite numbers,
+/// which JSON cannot represent.
+pub fn to_string(node: &JsonNode) -> Result<String, SerializeError> {
+    to_string_with_options(node, &SerializeOptions::default())
+}
+
+pub fn to_string_with_options(
+    node: &JsonNode,
+    options: &SerializeOptions,
+) -> Result<String, SerializeError> {
+    let mut out = String::new();
+    write_node(node, &mut out, options, false)?;
+    Ok(out)
+}
+
+/// Serializes `node` in a canonical form: compact, with object keys sorted and
+/// raw numbers rewritten the same way as parsed ones, so structurally equal
+/// documents produce identical text.
+pub fn to_string_canonical(node: &JsonNode) -> Result<String, SerializeError> {
+    let mut out = String::new();
+    write_node(node, &mut out, &SerializeOptions::default(), true)?;
+    Ok(out)
+}
+
+impl JsonNode {
+    /// Hashes the canonical serialization, so documents that differ only in
+    /// key order or number spelling hash the same.
+    #[cfg(feature = "std")]
+    pub fn canonical_hash(&self) -> u64 {
+        let options = SerializeOptions {
+            allow_non_finite: true,
+            ..Default::default()
+        };
+        let mut out = String::new();
+        // cannot fail: non-finite numbers are the only error and they are allowed
+        let _ = write_node(self, &mut out, &options, true);
+
+        let mut hasher = DefaultHasher::new();
+        out.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The length in bytes of `to_string`'s output, computed without building
+    /// it, for pre-sizing buffers. Non-finite numbers, which `to_string`
+    /// rejects, count as `NaN`, `Infinity` or `-Infinity`.
+    pub fn serialized_len(&self) -> usize {
+        match self {
+            JsonNode::Null => 4,
+            JsonNode::Bool(b) => {
+                if *b {
+                    4
+                } else {
+                    5
+                }
+            }
+            JsonNode::Number(n) => {
+                let options = SerializeOptions {
+                    allow_non_finite: true,
+                    ..Default::default()
+                };
+                let mut count = ByteCount(0);
+                let _ = write_number(*n, &mut count, &options);
+                count.0
+            }
+            JsonNode::RawNumber(text) => text.len(),
+            JsonNode::String(s) => escaped_len(s),
+            JsonNode::Array(vec) => {
+                let commas = vec.len().saturating_sub(1);
+                2 + commas + vec.iter().map(JsonNode::serialized_len).sum::<usize>()
+            }
+            JsonNode::Object(map) => {
+                let commas = map.len().saturating_sub(1);
+                let members: usize = map
+                    .iter()
+                    .map(|(key, value)| escaped_len(key) + 1 + value.serialized_len())
+                    .sum();
+                2 + commas + members
+            }
+        }
+    }
+
+    /// Renders any node as a string: strings as their contents, scalars as
+    /// their JSON text and containers as compact JSON. Non-finite numbers
+    /// become `NaN`, `Infinity` and `-Infinity`.
+    pub fn coerce_to_string(&self) -> String {
+        if let JsonNode::String(s) = self {
+            return s.clone();
+        }
+
+        let options = SerializeOptions {
+            allow_non_finite: true,
+            ..Default::default()
+        };
+        let mut out = String::new();
+        // cannot fail: non-finite numbers are the only error and they are allowed
+        let _ = write_node(self, &mut out, &options, false);
+        out
+    }
+
+    /// Writes the node to `w` as indented JSON, honouring every field of
+    /// `options`; `indent` defaults to two spaces when it is `None`.
+    #[cfg(feature = "std")]
+    pub fn write_pretty<W: Write>(
+        &self,
+        w: &mut W,
+        options: &SerializeOptions,
+    ) -> Result<(), SerializeError> {
+        let options = SerializeOptions {
+            indent: Some(options.indent.unwrap_or(2)),
+            ..options.clone()
+        };
+        let mut out = String::new();
+        write_node(self, &mut out, &options, false)?;
+
+        w.write_all(out.as_bytes()).map_err(|e| SerializeError {
+            message: format!("io error: {}", e),
+        })
+    }
+}
+
+/// Receives a document one event at a time from `JsonNode::write_to`, so
+/// an output format only has to say how each piece is written; the tree walk
+/// is shared. Separators and indentation are the writer's business.
+pub trait JsonWriter {
+    type Error;
+
+    fn null(&mut self) -> Result<(), Self::Error>;
+    fn bool(&mut self, b: bool) -> Result<(), Self::Error>;
+    fn number(&mut self, n: Number) -> Result<(), Self::Error>;
+    /// The source text of a `RawNumber`.
+    fn raw_number(&mut self, text: &str) -> Result<(), Self::Error>;
+    fn string(&mut self, s: &str) -> Result<(), Self::Error>;
+    fn begin_array(&mut self, len: usize) -> Result<(), Self::Error>;
+    fn end_array(&mut self) -> Result<(), Self::Error>;
+    fn begin_object(&mut self, len: usize) -> Result<(), Self::Error>;
+    /// Called before each member's value.
+    fn key(&mut self, key: &str) -> Result<(), Self::Error>;
+    fn end_object(&mut self) -> Result<(), Self::Error>;
+
+    /// Whether object members should arrive in key order rather than map order.
+    fn sort_keys(&self) -> bool {
+        false
+    }
+}
+
+impl JsonNode {
+    /// Walks the tree depth first, feeding every value to `writer`.
+    pub fn write_to<W: JsonWriter>(&self, writer: &mut W) -> Result<(), W::Error> {
+        match self {
+            JsonNode::String(s) => writer.string(s),
+            JsonNode::Number(n) => writer.number(*n),
+            JsonNode::RawNumber(s) => writer.raw_number(s),
+            JsonNode::Array(vec) => {
+                writer.begin_array(vec.len())?;
+                for item in vec {
+                    item.write_to(writer)?;
+                }
+                writer.end_array()
+            }
+            JsonNode::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                if writer.sort_keys() {
+                    entries.sort_by(|a, b| a.0.cmp(b.0));
+                }
+
+                writer.begin_object(entries.len())?;
+                for (key, value) in entries {
+                    writer.key(key)?;
+                    value.write_to(writer)?;
+                }
+                writer.end_object()
+            }
+            JsonNode::Bool(b) => writer.bool(*b),
+            JsonNode::Null => writer.null(),
+        }
+    }
+}
+
+fn write_node(
+    node: &JsonNode,
+    out: &mut String,
+    options: &SerializeOptions,
+    canonical: bool,
+) -> Result<(), SerializeError> {
+    node.write_to(&mut TextWriter {
+        out,
+        options,
+        canonical,
+        containers: vec![],
+        after_key: false,
+    })
+}
+
+/// The JSON text encoder behind `to_string`, `to_string_canonical` and
+/// `write_pretty`.
+struct TextWriter<'w> {
+    out: &'w mut String,
+    options: &'w SerializeOptions,
+    canonical: bool,
+    /// Per open container, how many children have been written so far.
+    containers: Vec<usize>,
+    /// A key was just written, so the next value needs no separator.
+    after_key: bool,
+}
+
+impl TextWriter<'_> {
+    /// Writes the separator before an array element or object key.
+    fn separator(&mut self) {
+        if let Some(count) = self.containers.last_mut() {
+            if *count > 0 {
+                self.out.push(',');
+            }
+            *count += 1;
+            self.newline(self.containers.len());
+        }
+    }
+
+    /// Writes the separator before a value, unless it follows a key.
+    fn before_value(&mut self) {
+        if !core::mem::take(&mut self.after_key) {
+            self.separator();
+        }
+    }
+
+    fn newline(&mut self, depth: usize) {
+        if let Some(indent) = self.options.indent {
+            self.out.push('\n');
+            self.out.push_str(&" ".repeat(indent * depth));
+        }
+    }
+
+    fn close(&mut self, bracket: char) {
+        if let Some(count) = self.containers.pop() {
+            if count > 0 {
+                self.newline(self.containers.len());
+            }
+        }
+        self.out.push(bracket);
+    }
+}
+
+impl JsonWriter for TextWriter<'_> {
+    type Error = SerializeError;
+
+    fn null(&mut self) -> Result<(), SerializeError> {
+        self.before_value();
+        self.out.push_str("null");
+        Ok(())
+    }
+
+    fn bool(&mut self, b: bool) -> Result<(), SerializeError> {
+        self.before_value();
+        self.out.push_str(if b { "true" } else { "false" });
+        Ok(())
+    }
+
+    fn number(&mut self, n: Number) -> Result<(), SerializeError> {
+        self.before_value();
+        // -0 and 0 compare equal, so the canonical form writes both the same way
+        let n = if self.canonical && n == 0.0 {
+            Number::default()
+        } else {
+            n
+        };
+        write_number(n, self.out, self.options)
+    }
+
+    fn raw_number(&mut self, text: &str) -> Result<(), SerializeError> {
+        match parse_number(text) {
+            Some(n) if self.canonical => self.number(n),
+            _ => {
+                self.before_value();
+                self.out.push_str(text);
+                Ok(())
+            }
+        }
+    }
+
+    fn string(&mut self, s: &str) -> Result<(), SerializeError> {
+        self.before_value();
+        escape_into(s, self.out, self.options);
+        Ok(())
+    }
+
+    fn begin_array(&mut self, _len: usize) -> Result<(), SerializeError> {
+        self.before_value();
+        self.out.push('[');
+        self.containers.push(0);
+        Ok(())
+    }
+
+    fn end_array(&mut self) -> Result<(), SerializeError> {
+        self.close(']');
+        Ok(())
+    }
+
+    fn begin_object(&mut self, _len: usize) -> Result<(), SerializeError> {
+        self.before_value();
+        self.out.push('{');
+        self.containers.push(0);
+        Ok(())
+    }
+
+    fn key(&mut self, key: &str) -> Result<(), SerializeError> {
+        self.separator();
+        escape_into(key, self.out, self.options);
+        self.out.push(':');
+        if self.options.indent.is_some() {
+            self.out.push(' ');
+        }
+        self.after_key = true;
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> Result<(), SerializeError> {
+        self.close('}');
+        Ok(())
+    }
+
+    fn sort_keys(&self) -> bool {
+        self.canonical || self.options.sort_keys
+    }
+}
+
+/// Writes `n` to any `fmt::Write`, so `serialized_len` can measure it
+/// without building the text.
+pub(crate) fn write_number<W: core::fmt::Write>(
+    n: Number,
+    out: &mut W,
+    options: &SerializeOptions,
+) -> Result<(), SerializeError> {
+    if let Some(n) = n.integer() {
+        let _ = write!(out, "{}", n);
+        return Ok(());
+    }
+    let n = n.as_f64();
+    if !n.is_finite() {
+        if !options.allow_non_finite {
+            return Err(SerializeError {
+                message: format!("cannot represent {} in JSON", n),
+            });
+        }
+
+        let _ = out.write_str(if n.is_nan() {
+            "NaN"
+        } else if n > 0.0 {
+            "Infinity"
+        } else {
+            "-Infinity"
+        });
+    } else if is_integral(n) && n.abs() < 1e17 {
+        // integral values print without a trailing `.0`
+        let _ = write!(out, "{}", n);
+    } else {
+        // `Debug` gives the shortest round-tripping form and switches to
+        // exponent notation for very large and very small magnitudes
+        let _ = write!(out, "{:?}", n);
+    }
+    Ok(())
+}
+
+/// A `fmt::Write` that only counts the bytes written to it.
+struct ByteCount(usize);
+
+impl core::fmt::Write for ByteCount {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::GITHUB_EVENT,
+        parser::{parse, ParserBuilder},
+    };
+
+    #[test]
+    fn compact() {
+        let json = parse("[1, 2.5, -0.001, 1e300, \"a\\nb\", true, null, {\"k\": []}]").unwrap();
+        assert_eq!(
+            to_string(&json).unwrap(),
+            "[1,2.5,-0.001,1e300,\"a\\nb\",true,null,{\"k\":[]}]"
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        let json = parse(r#"{"b":[1.50,{"y":1,"x":-0}],"a":1E2}"#).unwrap();
+        assert_eq!(
+            to_string_canonical(&json).unwrap(),
+            r#"{"a":100,"b":[1.5,{"x":0,"y":1}]}"#
+        );
+    }
+
+    #[test]
+    fn serialized_len() {
+        let documents = [
+            GITHUB_EVENT,
+            "null",
+            "[]",
+            "{}",
+            r#"[true,false,-0.0,1e300,0.1,12345678901234567890,"tab\t\u0001é😀\"\\"]"#,
+            r#"{"a\nb":{"c":[1,[2,{}]]},"d":""}"#,
+        ];
+        for source in documents {
+            let json = parse(source).unwrap();
+            assert_eq!(
+                json.serialized_len(),
+                to_string(&json).unwrap().len(),
+                "{}",
+                source
+            );
+        }
+
+        let raw = ParserBuilder::new()
+            .raw_numbers(true)
+            .parse("[1.50E+2, 7]")
+            .unwrap();
+        assert_eq!(raw.serialized_len(), to_string(&raw).unwrap().len());
+        assert_eq!(JsonNode::from(f64::NEG_INFINITY).serialized_len(), 9);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn canonical_hash() {
+        let a = parse(r#"{"id":1,"tags":["x","y"],"meta":{"b":true,"a":null}}"#).unwrap();
+        let b = parse(r#"{"meta":{"a":null,"b":true},"tags":["x","y"],"id":1.0}"#).unwrap();
+        let c = parse(r#"{"id":1,"tags":["y","x"],"meta":{"b":true,"a":null}}"#).unwrap();
+
+        assert_eq!(a.canonical_hash(), b.canonical_hash());
+        assert_ne!(a.canonical_hash(), c.canonical_hash());
+    }
+
+    #[test]
+    fn ascii_only() {
+        let json = JsonNode::Array(vec![JsonNode::String("😀".to_string())]);
+        let options = SerializeOptions {
+            ascii_only: true,
+            ..Default::default()
+        };
+
+        let text = to_string_with_options(&json, &options).unwrap();
+        assert_eq!(text, r#"["\ud83d\ude00"]"#);
+        assert_eq!(parse(&text).unwrap(), json);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn write_pretty() {
+        let json = parse(r#"{"b":[1,{"é":null}],"a":{},"c":[]}"#).unwrap();
+        let options = SerializeOptions {
+            indent: Some(2),
+            sort_keys: true,
+            ascii_only: true,
+            ..Default::default()
+        };
+
+        let mut buf = Vec::new();
+        json.write_pretty(&mut buf, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "{\n  \"a\": {},\n  \"b\": [\n    1,\n    {\n      \"\\u00e9\": null\n    }\n  ],\n  \"c\": []\n}"
+        );
+    }
+
+    #[test]
+    fn coerce_to_string() {
+        let cases = [
+            (JsonNode::String("a \"b\"".to_string()), "a \"b\""),
+            (JsonNode::from(42.0), "42"),
+            (JsonNode::from(-0.5), "-0.5"),
+            (JsonNode::from(f64::NAN), "NaN"),
+            (JsonNode::RawNumber("1.50".to_string()), "1.50"),
+            (JsonNode::Bool(true), "true"),
+            (JsonNode::Bool(false), "false"),
+            (JsonNode::Null, "null"),
+            (parse(r#"[1, "x", [ ]]"#).unwrap(), r#"[1,"x",[]]"#),
+            (
+                parse(r#"{ "k" : {"n": null} }"#).unwrap(),
+                r#"{"k":{"n":null}}"#,
+            ),
+        ];
+
+        for (node, expected) in cases {
+            assert_eq!(node.coerce_to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn custom_writer() {
+        #[derive(Default)]
+        struct Counter {
+            scalars: usize,
+            containers: usize,
+            keys: Vec<String>,
+        }
+
+        impl JsonWriter for Counter {
+            type Error = ();
+
+            fn null(&mut self) -> Result<(), ()> {
+                self.scalars += 1;
+                Ok(())
+            }
+            fn bool(&mut self, _: bool) -> Result<(), ()> {
+                self.scalars += 1;
+                Ok(())
+            }
+            fn number(&mut self, _: Number) -> Result<(), ()> {
+                self.scalars += 1;
+                Ok(())
+            }
+            fn raw_number(&mut self, _: &str) -> Result<(), ()> {
+                self.scalars += 1;
+                Ok(())
+            }
+            fn string(&mut self, _: &str) -> Result<(), ()> {
+                self.scalars += 1;
+                Ok(())
+            }
+            fn begin_array(&mut self, _: usize) -> Result<(), ()> {
+                self.containers += 1;
+                Ok(())
+            }
+            fn end_array(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+            fn begin_object(&mut self, _: usize) -> Result<(), ()> {
+                self.containers += 1;
+                Ok(())
+            }
+            fn key(&mut self, key: &str) -> Result<(), ()> {
+                self.keys.push(key.to_string());
+                Ok(())
+            }
+            fn end_object(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+            fn sort_keys(&self) -> bool {
+                true
+            }
+        }
+
+        let json = parse(r#"{"b":[1,"x",null],"a":{"c":true}}"#).unwrap();
+        let mut counter = Counter::default();
+        json.write_to(&mut counter).unwrap();
+
+        assert_eq!(counter.scalars, 4);
+        assert_eq!(counter.containers, 3);
+        assert_eq!(counter.keys, ["a", "c", "b"]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let json = parse(GITHUB_EVENT).unwrap();
+        assert_eq!(parse(&to_string(&json).unwrap()).unwrap(), json);
+    }
+
+    #[test]
+    fn non_finite_is_an_error_by_default() {
+        let json = JsonNode::Array(vec![JsonNode::from(f64::NAN)]);
+
+        let err = to_string(&json).unwrap_err();
+        assert_eq!(err.message, "cannot represent NaN in JSON");
+
+        let json = JsonNode::from(f64::NEG_INFINITY);
+        assert!(to_string(&json).is_err());
+    }
+
+    #[test]
+    fn non_finite_allowed() {
+        let json = JsonNode::Array(vec![
+            JsonNode::from(f64::NAN),
+            JsonNode::from(f64::INFINITY),
+            JsonNode::from(f64::NEG_INFINITY),
+        ]);
+        let options = SerializeOptions {
+            allow_non_finite: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            to_string_with_options(&json, &options).unwrap(),
+            "[NaN,Infinity,-Infinity]"
+        );
+    }
+}
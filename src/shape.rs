@@ -0,0 +1,62 @@
+use crate::parser::JsonNode;
+
+impl JsonNode {
+    /// Checks this node against a template document.
+    ///
+    /// A string in `shape` names the type expected at that position
+    /// (`"string"`, `"number"`, `"boolean"`, `"null"`, `"array"`, `"object"`
+    /// or `"any"`); any other scalar only requires a value of the same type.
+    /// Every key of a shape object must be present and match, while extra keys
+    /// are allowed. A shape array with one element requires every element to
+    /// match it, and an empty shape array accepts any array.
+    pub fn matches_shape(&self, shape: &JsonNode) -> bool {
+        match shape {
+            JsonNode::String(expected) => expected == "any" || self.type_name() == expected,
+            JsonNode::Object(fields) => match self {
+                JsonNode::Object(map) => fields.iter().all(|(key, field)| match map.get(key) {
+                    Some(value) => value.matches_shape(field),
+                    None => false,
+                }),
+                _ => false,
+            },
+            JsonNode::Array(items) => match (self, items.first()) {
+                (JsonNode::Array(vec), Some(item)) => vec.iter().all(|v| v.matches_shape(item)),
+                (JsonNode::Array(_), None) => true,
+                _ => false,
+            },
+            _ => self.type_name() == shape.type_name(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fixtures::GITHUB_EVENT, parser::parse};
+
+    #[test]
+    fn repo_shape() {
+        let json = parse(GITHUB_EVENT).unwrap();
+        let repo = json.as_map().unwrap().get("repo").unwrap();
+
+        let shape = parse(r#"{"id":"number","name":"string","url":"string"}"#).unwrap();
+        assert!(repo.matches_shape(&shape));
+
+        let shape = parse(r#"{"id":"string","name":"string"}"#).unwrap();
+        assert!(!repo.matches_shape(&shape));
+
+        let shape = parse(r#"{"id":"number","owner":"string"}"#).unwrap();
+        assert!(!repo.matches_shape(&shape));
+    }
+
+    #[test]
+    fn array_shape() {
+        let shape = parse(r#"[{"a":"number"}]"#).unwrap();
+
+        assert!(parse(r#"[{"a":1},{"a":2,"b":3}]"#)
+            .unwrap()
+            .matches_shape(&shape));
+        assert!(!parse(r#"[{"a":1},{"a":"x"}]"#)
+            .unwrap()
+            .matches_shape(&shape));
+    }
+}
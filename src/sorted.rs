@@ -0,0 +1,59 @@
+use crate::parser::JsonNode;
+
+impl JsonNode {
+    /// Sorts the members of every object, at any depth, by key, so iterating
+    /// or serializing the tree is deterministic.
+    pub fn into_sorted(mut self) -> JsonNode {
+        self.walk_mut(|node| {
+            if let JsonNode::Object(map) = node {
+                map.sort_keys();
+            }
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::GITHUB_EVENT,
+        map::HasherKind,
+        parser::{parse, parse_with_options, ParseOptions},
+        ser::to_string,
+    };
+    use alloc::{string::String, vec::Vec};
+
+    #[test]
+    fn deterministic_output() {
+        // whichever hasher built the maps, the sorted form comes out the same
+        let fx = ParseOptions {
+            hasher: HasherKind::FxHash,
+            ..Default::default()
+        };
+        let texts: Vec<String> = [ParseOptions::default(), fx]
+            .iter()
+            .map(|options| {
+                let json = parse_with_options(GITHUB_EVENT, options).unwrap();
+                to_string(&json.into_sorted()).unwrap()
+            })
+            .collect();
+
+        assert!(texts.iter().all(|text| *text == texts[0]));
+        assert!(texts[0].starts_with(r#"{"actor":{"avatar_url":"#));
+        assert_eq!(parse(&texts[0]).unwrap(), parse(GITHUB_EVENT).unwrap());
+    }
+
+    #[test]
+    fn sorted_keys_at_every_level() {
+        let source = r#"{"b":{"z":1,"y":[{"d":1,"c":2}]},"a":null}"#;
+        let sorted = parse(source).unwrap().into_sorted();
+
+        let keys: Vec<&String> = sorted.as_map().unwrap().keys().collect();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(
+            to_string(&sorted).unwrap(),
+            r#"{"a":null,"b":{"y":[{"c":2,"d":1}],"z":1}}"#
+        );
+        assert_eq!(sorted, parse(source).unwrap());
+    }
+}
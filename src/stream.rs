@@ -0,0 +1,223 @@
+use crate::{
+    error::ParseError,
+    parser::{parse_from, JsonNode, JsonParser},
+    token::TokenType,
+};
+use alloc::string::String;
+
+/// Iterates over the members of a top-level object one at a time, without
+/// building the whole map. See `parse_key_value_stream`.
+pub struct KeyValueStream<'a> {
+    parser: JsonParser<'a>,
+    state: State,
+}
+
+enum State {
+    Start,
+    Members { after_comma: bool },
+    Done,
+}
+
+/// Streams the `(key, value)` pairs of a top-level object, so callers can
+/// keep only the members they need. Each value is still parsed in full.
+///
+/// The iterator yields an error, then stops, if the document is not an
+/// object or is malformed.
+pub fn parse_key_value_stream(source: &str) -> KeyValueStream<'_> {
+    KeyValueStream {
+        parser: JsonParser::new(source),
+        state: State::Start,
+    }
+}
+
+impl KeyValueStream<'_> {
+    fn step(&mut self) -> Result<Option<(String, JsonNode)>, ParseError> {
+        if let State::Start = self.state {
+            match self.parser.advance() {
+                Some(token) if token.token_type == TokenType::LeftCurlyBracket => {
+                    self.parser.open.push(token.index);
+                    self.state = State::Members { after_comma: false };
+                }
+                Some(token) => {
+                    return Err(ParseError::at(
+                        "expected an object",
+                        self.parser.source,
+                        token.index,
+                        token.line,
+                    ))
+                }
+                None => return Err(ParseError::eof("eof")),
+            }
+        }
+
+        let after_comma = match self.state {
+            State::Members { after_comma } => after_comma,
+            _ => return Ok(None),
+        };
+
+        let member = match self.parser.member(after_comma) {
+            Ok(member) => member,
+            Err(e) => return Err(self.parser.error(e)),
+        };
+        if member.is_none() {
+            self.state = State::Done;
+            return Ok(None);
+        }
+
+        match self.parser.member_separator() {
+            Ok(true) => self.state = State::Members { after_comma: true },
+            Ok(false) => self.state = State::Done,
+            Err(e) => return Err(self.parser.error(e)),
+        }
+        Ok(member)
+    }
+}
+
+impl Iterator for KeyValueStream<'_> {
+    type Item = Result<(String, JsonNode), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let State::Done = self.state {
+            return None;
+        }
+
+        match self.step() {
+            Ok(member) => member.map(Ok),
+            Err(e) => {
+                self.state = State::Done;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Iterates over the documents of JSON Lines (NDJSON) text. See `parse_lines`.
+pub struct JsonLines<'a> {
+    source: &'a str,
+    offset: usize,
+    line: usize,
+}
+
+/// Parses JSON Lines text: one document per line, blank lines skipped.
+///
+/// A malformed line yields an error carrying its line number and byte index
+/// in `source`, and iteration carries on with the next line.
+pub fn parse_lines(source: &str) -> JsonLines<'_> {
+    JsonLines {
+        source,
+        offset: 0,
+        line: 0,
+    }
+}
+
+impl Iterator for JsonLines<'_> {
+    type Item = Result<JsonNode, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.offset >= self.source.len() {
+                return None;
+            }
+
+            let start = self.offset;
+            let end = match self.source[start..].find('\n') {
+                Some(i) => start + i,
+                None => self.source.len(),
+            };
+            self.offset = end + 1;
+            self.line += 1;
+
+            let text = &self.source[start..end];
+            if text.trim().is_empty() {
+                continue;
+            }
+
+            let result = match parse_from(text, 0) {
+                Ok((json, end)) if text[end..].trim().is_empty() => Ok(json),
+                Ok((_, end)) => Err(ParseError::at(
+                    "unexpected text after value",
+                    text,
+                    end + (text[end..].len() - text[end..].trim_start().len()),
+                    1,
+                )),
+                Err(e) => Err(e),
+            };
+
+            // positions are relative to the line; make them relative to the source
+            return Some(result.map_err(|e| ParseError {
+                index: e.index.map(|i| start + i),
+                line: Some(self.line),
+                ..e
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::GITHUB_EVENT;
+
+    #[test]
+    fn filter_fixture_keys() {
+        let kept: Vec<(String, JsonNode)> = parse_key_value_stream(GITHUB_EVENT)
+            .map(|member| member.unwrap())
+            .filter(|(key, _)| key == "type" || key == "public")
+            .collect();
+
+        assert_eq!(
+            kept,
+            vec![
+                (
+                    "type".to_string(),
+                    JsonNode::String("CreateEvent".to_string())
+                ),
+                ("public".to_string(), JsonNode::Bool(true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_every_member() {
+        assert_eq!(parse_key_value_stream(GITHUB_EVENT).count(), 7);
+        assert_eq!(parse_key_value_stream(" {} ").count(), 0);
+    }
+
+    #[test]
+    fn stops_after_error() {
+        let mut stream = parse_key_value_stream(r#"{"a":1,"b":}"#);
+
+        assert_eq!(stream.next().unwrap().unwrap().0, "a");
+        assert_eq!(
+            stream.next().unwrap().unwrap_err().message,
+            "expected a value"
+        );
+        assert!(stream.next().is_none());
+
+        let err = parse_key_value_stream("[1]").next().unwrap().unwrap_err();
+        assert_eq!(err.message, "expected an object");
+    }
+
+    #[test]
+    fn lines_isolate_errors() {
+        let source = "{\"id\":1}\n{\"id\":}\n\n{\"id\":3}\r\n";
+        let results: Vec<_> = parse_lines(source).collect();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap()["id"], JsonNode::from(1.0));
+        assert_eq!(results[2].as_ref().unwrap()["id"], JsonNode::from(3.0));
+
+        let err = results[1].as_ref().unwrap_err();
+        assert_eq!(err.line, Some(2));
+        assert_eq!(err.index, Some(15));
+        assert_eq!(err.column, Some(7));
+    }
+
+    #[test]
+    fn lines_reject_trailing_text() {
+        let err = parse_lines("1\n2 3\n").nth(1).unwrap().unwrap_err();
+        assert_eq!(err.message, "unexpected text after value");
+        assert_eq!(err.line, Some(2));
+        assert_eq!(err.index, Some(4));
+    }
+}
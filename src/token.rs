@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use core::fmt::Display;
 
 #[derive(PartialEq, Debug, Clone)]
 pub struct Token<'a> {
@@ -7,10 +7,56 @@ pub struct Token<'a> {
     pub token_type: TokenType<'a>,
 }
 
+impl Token<'_> {
+    /// The length in bytes of the token's source text, quotes included for
+    /// strings. An error token covers the text its error is about.
+    pub fn len(&self) -> usize {
+        match self.token_type {
+            TokenType::String { text }
+            | TokenType::Number { text }
+            | TokenType::Error { text, .. }
+            | TokenType::LineComment { text }
+            | TokenType::BlockComment { text } => text.len(),
+            TokenType::Colon
+            | TokenType::Comma
+            | TokenType::LeftSquareBracket
+            | TokenType::RightSquareBracket
+            | TokenType::LeftCurlyBracket
+            | TokenType::RightCurlyBracket => 1,
+            TokenType::True | TokenType::Null => 4,
+            TokenType::False => 5,
+        }
+    }
+
+    /// Whether the token covers no source text, as only an error token can.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The byte offset just past the token, so `index..end()` is its span.
+    ///
+    /// ```
+    /// use json_parser::{Tokenizer, TokenType};
+    ///
+    /// let src = r#"{"key": -1.5}"#;
+    /// let token = Tokenizer::new(src).nth(3).unwrap();
+    /// assert!(matches!(token.token_type, TokenType::Number { .. }));
+    /// assert_eq!(token.len(), 4);
+    /// assert_eq!(&src[token.index..token.end()], "-1.5");
+    /// ```
+    pub fn end(&self) -> usize {
+        self.index + self.len()
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum TokenType<'a> {
-    String { text: &'a str },
-    Number { text: &'a str },
+    String {
+        text: &'a str,
+    },
+    Number {
+        text: &'a str,
+    },
 
     Colon,
     Comma,
@@ -24,11 +70,60 @@ pub enum TokenType<'a> {
     True,
     False,
     Null,
-    Error { message: &'a str },
+    Error {
+        kind: LexErrorKind,
+        text: &'a str,
+    },
+
+    /// `// ...` or `# ...`, without the line break. Only produced with
+    /// `TokenizerOptions::emit_comments`.
+    LineComment {
+        text: &'a str,
+    },
+    /// `/* ... */`. Only produced with `TokenizerOptions::emit_comments`.
+    BlockComment {
+        text: &'a str,
+    },
+}
+
+/// Why the tokenizer produced an `Error` token. More kinds may be added, so
+/// matches need a wildcard arm.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum LexErrorKind {
+    UnterminatedString,
+    UnknownKeyword,
+    InvalidNumber,
+    ControlCharInString,
+    InvalidEscape,
+    UnexpectedControlChar,
+    InvalidWhitespace,
+    UnterminatedComment,
+}
+
+impl LexErrorKind {
+    pub fn message(&self) -> &'static str {
+        match self {
+            LexErrorKind::UnterminatedString => "unterminated string",
+            LexErrorKind::UnknownKeyword => "unknown keyword",
+            LexErrorKind::InvalidNumber => "invalid number",
+            LexErrorKind::ControlCharInString => "control character in string",
+            LexErrorKind::InvalidEscape => "invalid escape",
+            LexErrorKind::UnexpectedControlChar => "unexpected control character",
+            LexErrorKind::InvalidWhitespace => "invalid whitespace character",
+            LexErrorKind::UnterminatedComment => "unterminated comment",
+        }
+    }
+}
+
+impl Display for LexErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.message())
+    }
 }
 
 impl Display for Token<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self.token_type {
             TokenType::String { text, .. } => write!(f, "STR\"{}\"", text),
             TokenType::Number { text, .. } => write!(f, "NUM\"{}\"", text),
@@ -41,7 +136,10 @@ impl Display for Token<'_> {
             TokenType::True => write!(f, "<TRUE>"),
             TokenType::False => write!(f, "<FALSE>"),
             TokenType::Null => write!(f, "<NULL>"),
-            TokenType::Error { message, .. } => write!(f, "ERR\"{}\"", message),
+            TokenType::Error { kind, text } => write!(f, "ERR<{}>\"{}\"", kind, text),
+            TokenType::LineComment { text } | TokenType::BlockComment { text } => {
+                write!(f, "COMMENT\"{}\"", text)
+            }
         }
     }
 }
@@ -1,10 +1,41 @@
-use crate::token::{Token, TokenType};
+use crate::{
+    parser::CommentStyle,
+    token::{LexErrorKind, Token, TokenType},
+};
+
+const WORD_SIZE: usize = 8;
+const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+/// Sets the high bit of every byte lane in `word` that equals `byte`.
+fn lanes_equal(word: u64, byte: u8) -> u64 {
+    let x = word ^ (LOW_BITS * byte as u64);
+    !(((x & !HIGH_BITS) + !HIGH_BITS) | x) & HIGH_BITS
+}
 
 pub struct Tokenizer<'a> {
     start: usize,
     current: usize,
     line: usize,
     src: &'a str,
+    options: TokenizerOptions,
+    /// The token lexed by `peek_token`, handed out by the next `next`.
+    peeked: Option<Token<'a>>,
+}
+
+/// Lexical extensions beyond strict JSON, all off by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenizerOptions {
+    /// Which comments to skip like whitespace.
+    pub comments: CommentStyle,
+    /// Accept strings delimited by `'` as well as `"`.
+    pub single_quotes: bool,
+    /// Return the comments enabled by `comments` as `LineComment` and
+    /// `BlockComment` tokens instead of skipping them.
+    pub emit_comments: bool,
+    /// Lex a run of identifier characters as one word, so that `nullable` is
+    /// a single unknown keyword rather than `null` followed by `able`.
+    pub identifiers: bool,
 }
 
 impl<'a> Tokenizer<'a> {
@@ -14,114 +45,94 @@ impl<'a> Tokenizer<'a> {
             current: 0,
             line: 1,
             src,
+            options: TokenizerOptions::default(),
+            peeked: None,
         }
     }
 
-    pub fn next(&mut self) -> Option<Token<'a>> {
-        self.skip_white_spaces();
+    pub fn with_options(mut self, options: TokenizerOptions) -> Tokenizer<'a> {
+        self.options = options;
+        self
+    }
 
-        // NUMBER
-        if self.check_byte(b'-') || self.is_digit() {
-            return Some(self.number());
+    /// Starts tokenizing `src` at byte `offset`; token indices stay relative to the whole of `src`.
+    pub fn new_at(src: &'a str, offset: usize) -> Tokenizer<'a> {
+        Tokenizer {
+            start: offset,
+            current: offset,
+            line: src[..offset].matches('\n').count() + 1,
+            src,
+            options: TokenizerOptions::default(),
+            peeked: None,
         }
+    }
 
-        // STRING
-        if self.check_byte(b'"') {
-            return Some(self.string());
+    /// The byte offset just past the last token returned by `next`, or the
+    /// start of the peeked token if there is one.
+    pub fn position(&self) -> usize {
+        match &self.peeked {
+            Some(token) => token.index,
+            None => self.current,
         }
-        let c = self.peek();
-        if c.is_none() {
-            return None;
+    }
+
+    /// Lexes the next token without consuming it: the following `next`
+    /// returns the same token.
+    ///
+    /// ```
+    /// use json_parser::Tokenizer;
+    ///
+    /// let mut tokenizer = Tokenizer::new("[true]");
+    /// tokenizer.next();
+    /// let index = tokenizer.peek_token().unwrap().index;
+    /// assert_eq!(index, 1);
+    /// assert_eq!(tokenizer.position(), 1);
+    /// assert_eq!(tokenizer.next().unwrap().index, index);
+    /// ```
+    pub fn peek_token(&mut self) -> Option<&Token<'a>> {
+        if self.peeked.is_none() {
+            self.peeked = self.next();
         }
+        self.peeked.as_ref()
+    }
 
-        let index = self.current;
-        match c.unwrap() {
-            b'{' => {
-                self.advance();
-                Some(Token {
-                    line: self.line,
-                    index,
-                    token_type: TokenType::LeftCurlyBracket,
-                })
-            }
-            b'}' => {
-                self.advance();
-                Some(Token {
-                    line: self.line,
-                    index,
-                    token_type: TokenType::RightCurlyBracket,
-                })
-            }
-            b'[' => {
-                self.advance();
-                Some(Token {
-                    line: self.line,
-                    index,
-                    token_type: TokenType::LeftSquareBracket,
-                })
-            }
-            b']' => {
-                self.advance();
-                Some(Token {
-                    line: self.line,
-                    index,
-                    token_type: TokenType::RightSquareBracket,
-                })
-            }
-            b':' => {
-                self.advance();
-                Some(Token {
-                    line: self.line,
-                    index,
-                    token_type: TokenType::Colon,
-                })
-            }
-            b',' => {
-                self.advance();
-                Some(Token {
-                    line: self.line,
-                    index,
-                    token_type: TokenType::Comma,
-                })
-            }
-            b't' => {
-                if self.check("true") {
-                    self.current += 4;
-                    Some(Token {
-                        line: self.line,
-                        index,
-                        token_type: TokenType::True,
-                    })
-                } else {
-                    Some(self.unknown_keyword())
-                }
-            }
-            b'f' => {
-                if self.check("false") {
-                    self.current += 5;
-                    Some(Token {
-                        line: self.line,
-                        index,
-                        token_type: TokenType::False,
-                    })
-                } else {
-                    Some(self.unknown_keyword())
+    /// Skips ahead to the next `,`, `}` or `]` that is not inside a nested
+    /// array or object, so that `next` returns it. Strings are stepped over
+    /// whole. Call it after an error token to get back in sync.
+    ///
+    /// ```
+    /// use json_parser::Tokenizer;
+    ///
+    /// let src = r#"[1, nope [2, "]"], 3]"#;
+    /// let mut tokenizer = Tokenizer::new(src);
+    /// for _ in 0..4 {
+    ///     tokenizer.next(); // `[`, `1`, `,` and the bad keyword
+    /// }
+    /// tokenizer.recover();
+    ///
+    /// let comma = tokenizer.next().unwrap();
+    /// assert_eq!(&src[comma.index..], ", 3]");
+    /// ```
+    pub fn recover(&mut self) {
+        let mut depth = 0usize;
+        loop {
+            let token = match self.next() {
+                Some(token) => token,
+                None => return,
+            };
+            match token.token_type {
+                TokenType::LeftSquareBracket | TokenType::LeftCurlyBracket => depth += 1,
+                TokenType::RightSquareBracket | TokenType::RightCurlyBracket if depth > 0 => {
+                    depth -= 1
                 }
-            }
-
-            b'n' => {
-                if self.check("null") {
-                    self.current += 4;
-                    Some(Token {
-                        line: self.line,
-                        index,
-                        token_type: TokenType::Null,
-                    })
-                } else {
-                    Some(self.unknown_keyword())
+                TokenType::Comma | TokenType::RightSquareBracket | TokenType::RightCurlyBracket
+                    if depth == 0 =>
+                {
+                    self.peeked = Some(token);
+                    return;
                 }
+                _ => {}
             }
-            _ => Some(self.unknown_keyword()),
         }
     }
 
@@ -129,6 +140,11 @@ impl<'a> Tokenizer<'a> {
         self.start = self.current;
         if self.check_byte(b'-') {
             self.advance(); // consume minus sign
+                            // keep a word like `-Infinity` in one token
+            if self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                self.current = self.start;
+                return self.unknown_keyword();
+            }
         }
 
         if self.is_zero() {
@@ -138,20 +154,15 @@ impl<'a> Tokenizer<'a> {
             while !self.at_end() && self.is_digit() {
                 self.advance();
             }
-        }
-
-        if self.at_end() {
-            return Token {
-                line: self.line,
-                index: self.start,
-                token_type: TokenType::Number {
-                    text: &self.src[self.start..self.current],
-                },
-            };
+        } else {
+            return self.invalid_number();
         }
 
         if self.check_byte(b'.') {
             self.advance(); // consume the dot
+            if !self.is_digit() {
+                return self.invalid_number();
+            }
             while !self.at_end() && self.is_digit() {
                 self.advance();
             }
@@ -162,11 +173,20 @@ impl<'a> Tokenizer<'a> {
             if self.check_byte(b'+') || self.check_byte(b'-') {
                 self.advance(); // consume the + or -
             }
+            if !self.is_digit() {
+                return self.invalid_number();
+            }
             while !self.at_end() && self.is_digit() {
                 self.advance();
             }
         }
 
+        // a well-formed number cannot be followed directly by more number
+        // characters, as in `01`, `1.2.3` or `1e1e1`
+        if self.is_number_char() {
+            return self.invalid_number();
+        }
+
         Token {
             line: self.line,
             index: self.start,
@@ -176,28 +196,68 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// Consumes the rest of a malformed number and reports all of it.
+    fn invalid_number(&mut self) -> Token<'a> {
+        while self.is_number_char() {
+            self.advance();
+        }
+
+        Token {
+            line: self.line,
+            index: self.start,
+            token_type: TokenType::Error {
+                kind: LexErrorKind::InvalidNumber,
+                text: &self.src[self.start..self.current],
+            },
+        }
+    }
+
+    fn is_number_char(&self) -> bool {
+        match self.peek() {
+            Some(c) => c.is_ascii_digit() || matches!(c, b'.' | b'e' | b'E' | b'+' | b'-'),
+            None => false,
+        }
+    }
+
     fn string(&mut self) -> Token<'a> {
         self.start = self.current;
-        self.advance(); // consume the "
+        let quote = self.peek().unwrap();
+        self.advance(); // consume the opening quote
+        let mut control_char = false;
+        // the span of the first bad escape sequence, reported instead of the string
+        let mut bad_escape = None;
 
         while !self.at_end() {
-            if self.check_byte(b'"') {
+            if self.check_byte(quote) {
                 self.advance();
+                let text = &self.src[self.start..self.current];
+                let (index, token_type) = if let Some((from, to)) = bad_escape {
+                    let kind = LexErrorKind::InvalidEscape;
+                    let text = &self.src[from..to];
+                    (from, TokenType::Error { kind, text })
+                } else if control_char {
+                    let kind = LexErrorKind::ControlCharInString;
+                    (self.start, TokenType::Error { kind, text })
+                } else {
+                    (self.start, TokenType::String { text })
+                };
                 return Token {
                     line: self.line,
-                    index: self.start,
-                    token_type: TokenType::String {
-                        text: &self.src[self.start..self.current],
-                    },
+                    index,
+                    token_type,
                 };
             }
 
-            if self.check_byte(b'\\') {
-                self.advance();
+            if self.peek().unwrap() < 0x20 {
+                control_char = true;
+            }
 
-                // if self.check_byte(b'"') {
+            if self.check_byte(b'\\') {
+                let from = self.current;
                 self.advance();
-                // }
+                if !self.escape(quote) && bad_escape.is_none() {
+                    bad_escape = Some((from, self.current));
+                }
                 continue;
             }
 
@@ -208,12 +268,127 @@ impl<'a> Tokenizer<'a> {
             line: self.line,
             index: self.start,
             token_type: TokenType::Error {
-                message: "unterminated string",
+                kind: LexErrorKind::UnterminatedString,
+                text: &self.src[self.start..],
             },
         }
     }
 
+    /// Consumes the escape sequence after a backslash, stopping at the first
+    /// byte that cannot be part of it. Returns whether the sequence is valid.
+    fn escape(&mut self, quote: u8) -> bool {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return false,
+        };
+        if c == quote || matches!(c, b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't') {
+            self.advance();
+            return true;
+        }
+        if c != b'u' {
+            self.skip_bad_escape_byte(quote);
+            return false;
+        }
+
+        self.advance();
+        for _ in 0..4 {
+            if !self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                self.skip_bad_escape_byte(quote);
+                return false;
+            }
+            self.advance();
+        }
+        true
+    }
+
+    /// Includes the offending byte in the error text, unless the string loop
+    /// still has to see it: a quote, a control character or part of a
+    /// multi-byte character.
+    fn skip_bad_escape_byte(&mut self, quote: u8) {
+        if let Some(c) = self.peek() {
+            if c != quote && (0x20..0x80).contains(&c) {
+                self.advance();
+            }
+        }
+    }
+
     fn skip_white_spaces(&mut self) {
+        loop {
+            self.skip_blanks();
+            if self.options.comments == CommentStyle::None
+                || self.options.emit_comments
+                || !self.skip_comment()
+            {
+                break;
+            }
+        }
+    }
+
+    /// Skips one comment starting at the current position, returning whether there was one.
+    /// An unterminated block comment is not skipped, so that `next` reports it.
+    fn skip_comment(&mut self) -> bool {
+        let slash = self.options.comments.slash();
+        if (slash && self.check("//")) || (self.options.comments.hash() && self.check_byte(b'#')) {
+            while !self.at_end() && !self.check_byte(b'\n') {
+                self.advance();
+            }
+            return true;
+        }
+
+        if slash && self.check("/*") {
+            let (start, line) = (self.current, self.line);
+            self.current += 2;
+            while !self.at_end() && !self.check("*/") {
+                if self.check_byte(b'\n') {
+                    self.line += 1;
+                }
+                self.advance();
+            }
+            if self.at_end() {
+                self.current = start;
+                self.line = line;
+                return false;
+            }
+            self.current += 2;
+            return true;
+        }
+
+        false
+    }
+
+    fn skip_blanks(&mut self) {
+        if !self.is_space() {
+            return;
+        }
+
+        // Pretty-printed documents have long runs of indentation, so skip
+        // whole words at a time before falling back to single bytes.
+        let bytes = self.src.as_bytes();
+        while self.current + WORD_SIZE <= bytes.len() {
+            let mut word = [0; WORD_SIZE];
+            word.copy_from_slice(&bytes[self.current..self.current + WORD_SIZE]);
+            let word = u64::from_le_bytes(word);
+
+            let newlines = lanes_equal(word, b'\n');
+            let spaces = newlines
+                | lanes_equal(word, b' ')
+                | lanes_equal(word, b'\t')
+                | lanes_equal(word, b'\r');
+
+            if spaces == HIGH_BITS {
+                self.line += newlines.count_ones() as usize;
+                self.current += WORD_SIZE;
+                continue;
+            }
+
+            // Only the leading run of whitespace lanes belongs to this gap.
+            let run = (!spaces & HIGH_BITS).trailing_zeros() as usize / 8;
+            let run_mask = (1u64 << (run * 8)) - 1;
+            self.line += (newlines & run_mask).count_ones() as usize;
+            self.current += run;
+            return;
+        }
+
         while self.current < self.src.len() {
             if self.peek().unwrap() == b'\n' {
                 self.line += 1;
@@ -226,6 +401,28 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// Lexes a whole identifier and only then checks it for a keyword.
+    fn identifier(&mut self) -> Token<'a> {
+        let index = self.current;
+        let word = &self.src[index..];
+        let len = word
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .unwrap_or(word.len());
+
+        let token_type = match &word[..len] {
+            "true" => TokenType::True,
+            "false" => TokenType::False,
+            "null" => TokenType::Null,
+            _ => return self.unknown_keyword(),
+        };
+        self.current += len;
+        Token {
+            line: self.line,
+            index,
+            token_type,
+        }
+    }
+
     fn unknown_keyword(&mut self) -> Token<'a> {
         self.start = self.current;
         while !self.at_end() {
@@ -235,14 +432,16 @@ impl<'a> Tokenizer<'a> {
                 break;
             }
 
-            if self.is_space() {
+            if self.is_space() || self.is_unicode_space() {
                 break;
             }
 
             match c.unwrap() {
-                b'{' | b'}' | b'[' | b']' | b',' | b':' => {
+                // stop at the next string too, so it still tokenizes on its own
+                b'{' | b'}' | b'[' | b']' | b',' | b':' | b'"' => {
                     break;
                 }
+                b'\'' if self.options.single_quotes => break,
                 _ => self.advance(),
             }
         }
@@ -251,7 +450,8 @@ impl<'a> Tokenizer<'a> {
             line: self.line,
             index: self.start,
             token_type: TokenType::Error {
-                message: "unknown keyword",
+                kind: LexErrorKind::UnknownKeyword,
+                text: &self.src[self.start..self.current],
             },
         };
     }
@@ -263,6 +463,15 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// Whether a non-ASCII whitespace character such as U+00A0 starts here.
+    fn is_unicode_space(&self) -> bool {
+        self.src.is_char_boundary(self.current)
+            && self.src[self.current..]
+                .chars()
+                .next()
+                .is_some_and(|c| !c.is_ascii() && c.is_whitespace())
+    }
+
     fn is_digit(&self) -> bool {
         self.is_zero() || self.is_1to9()
     }
@@ -311,6 +520,180 @@ impl<'a> Tokenizer<'a> {
     }
 }
 
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if let Some(token) = self.peeked.take() {
+            return Some(token);
+        }
+        self.skip_white_spaces();
+
+        // COMMENT
+        if self.options.emit_comments {
+            let (index, line) = (self.current, self.line);
+            let block = self.check("/*");
+            if self.skip_comment() {
+                let text = &self.src[index..self.current];
+                let token_type = if block {
+                    TokenType::BlockComment { text }
+                } else {
+                    TokenType::LineComment { text }
+                };
+                return Some(Token {
+                    line,
+                    index,
+                    token_type,
+                });
+            }
+        }
+
+        // only an unterminated block comment is left unskipped; it runs to the end
+        if self.options.comments.slash() && self.check("/*") {
+            let (index, line) = (self.current, self.line);
+            let text = &self.src[index..];
+            self.line += text.matches('\n').count();
+            self.current = self.src.len();
+            return Some(Token {
+                line,
+                index,
+                token_type: TokenType::Error {
+                    kind: LexErrorKind::UnterminatedComment,
+                    text,
+                },
+            });
+        }
+
+        // NUMBER
+        if self.check_byte(b'-') || self.is_digit() {
+            return Some(self.number());
+        }
+
+        // STRING
+        if self.check_byte(b'"') || (self.options.single_quotes && self.check_byte(b'\'')) {
+            return Some(self.string());
+        }
+        let c = self.peek();
+        if c.is_none() {
+            return None;
+        }
+
+        let index = self.current;
+        match c.unwrap() {
+            b'{' => {
+                self.advance();
+                Some(Token {
+                    line: self.line,
+                    index,
+                    token_type: TokenType::LeftCurlyBracket,
+                })
+            }
+            b'}' => {
+                self.advance();
+                Some(Token {
+                    line: self.line,
+                    index,
+                    token_type: TokenType::RightCurlyBracket,
+                })
+            }
+            b'[' => {
+                self.advance();
+                Some(Token {
+                    line: self.line,
+                    index,
+                    token_type: TokenType::LeftSquareBracket,
+                })
+            }
+            b']' => {
+                self.advance();
+                Some(Token {
+                    line: self.line,
+                    index,
+                    token_type: TokenType::RightSquareBracket,
+                })
+            }
+            b':' => {
+                self.advance();
+                Some(Token {
+                    line: self.line,
+                    index,
+                    token_type: TokenType::Colon,
+                })
+            }
+            b',' => {
+                self.advance();
+                Some(Token {
+                    line: self.line,
+                    index,
+                    token_type: TokenType::Comma,
+                })
+            }
+            b't' | b'f' | b'n' if self.options.identifiers => Some(self.identifier()),
+            b't' => {
+                if self.check("true") {
+                    self.current += 4;
+                    Some(Token {
+                        line: self.line,
+                        index,
+                        token_type: TokenType::True,
+                    })
+                } else {
+                    Some(self.unknown_keyword())
+                }
+            }
+            b'f' => {
+                if self.check("false") {
+                    self.current += 5;
+                    Some(Token {
+                        line: self.line,
+                        index,
+                        token_type: TokenType::False,
+                    })
+                } else {
+                    Some(self.unknown_keyword())
+                }
+            }
+
+            b'n' => {
+                if self.check("null") {
+                    self.current += 4;
+                    Some(Token {
+                        line: self.line,
+                        index,
+                        token_type: TokenType::Null,
+                    })
+                } else {
+                    Some(self.unknown_keyword())
+                }
+            }
+            c if c < 0x20 || c == 0x7f => {
+                self.advance();
+                Some(Token {
+                    line: self.line,
+                    index,
+                    token_type: TokenType::Error {
+                        kind: LexErrorKind::UnexpectedControlChar,
+                        text: &self.src[index..self.current],
+                    },
+                })
+            }
+            // JSON allows only ASCII whitespace, so NBSP and friends are errors
+            _ if self.is_unicode_space() => {
+                self.current += self.src[index..].chars().next().map_or(1, char::len_utf8);
+                Some(Token {
+                    line: self.line,
+                    index,
+                    token_type: TokenType::Error {
+                        kind: LexErrorKind::InvalidWhitespace,
+                        text: &self.src[index..self.current],
+                    },
+                })
+            }
+            _ => Some(self.unknown_keyword()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -400,19 +783,332 @@ mod tests {
         assert_eq!(&actual, &expected);
     }
 
+    #[test]
+    fn string_with_escaped_nul() {
+        let mut tokenizer = Tokenizer::new(r#"["a\u0000b",1]"#);
+        tokenizer.next();
+
+        let expected = Token {
+            line: 1,
+            index: 1,
+            token_type: TokenType::String {
+                text: r#""a\u0000b""#,
+            },
+        };
+        assert_eq!(tokenizer.next().unwrap(), expected);
+        assert_eq!(tokenizer.next().unwrap().token_type, TokenType::Comma);
+    }
+
     #[test]
     fn unterminated_string() {
         let expected = Token {
             line: 1,
             index: 0,
             token_type: TokenType::Error {
-                message: "unterminated string",
+                kind: LexErrorKind::UnterminatedString,
+                text: r#""string"#,
             },
         };
         let actual = Tokenizer::new(r#""string"#).next().unwrap();
         assert_eq!(&actual, &expected);
     }
 
+    #[test]
+    fn unterminated_string_kind() {
+        let actual = Tokenizer::new(r#"  "abc\"#).next().unwrap();
+        match actual.token_type {
+            TokenType::Error { kind, text } => {
+                assert_eq!(kind, LexErrorKind::UnterminatedString);
+                assert_eq!(text, r#""abc\"#);
+            }
+            _ => panic!("expected an error token, got {}", actual),
+        }
+    }
+
+    #[test]
+    fn control_char_in_string() {
+        let actual = Tokenizer::new("\"a\tb\"").next().unwrap();
+        assert_eq!(
+            actual.token_type,
+            TokenType::Error {
+                kind: LexErrorKind::ControlCharInString,
+                text: "\"a\tb\"",
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        let invalid = |src| {
+            let token = Tokenizer::new(src).next().unwrap();
+            match token.token_type {
+                TokenType::Error {
+                    kind: LexErrorKind::InvalidEscape,
+                    text,
+                } => (token.index, text),
+                _ => panic!("expected an invalid escape, got {}", token),
+            }
+        };
+
+        assert_eq!(invalid(r#""\q""#), (1, r"\q"));
+        assert_eq!(invalid(r#""ab\u12zz""#), (3, r"\u12z"));
+        assert_eq!(invalid(r#""\u12""#), (1, r"\u12"));
+        assert_eq!(invalid(r#""\n\x\y""#), (3, r"\x"));
+
+        let valid = r#""\" \\ \/ \b \f \n \r \t \u00aF""#;
+        assert_eq!(
+            Tokenizer::new(valid).next().unwrap().token_type,
+            TokenType::String { text: valid }
+        );
+
+        // a backslash at the end of the input escapes nothing
+        let token = Tokenizer::new(r#""\"#).next().unwrap();
+        assert!(matches!(
+            token.token_type,
+            TokenType::Error {
+                kind: LexErrorKind::UnterminatedString,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn control_char_between_tokens() {
+        let mut tokenizer = Tokenizer::new("1 \0 2");
+        tokenizer.next();
+
+        let expected = Token {
+            line: 1,
+            index: 2,
+            token_type: TokenType::Error {
+                kind: LexErrorKind::UnexpectedControlChar,
+                text: "\0",
+            },
+        };
+        assert_eq!(tokenizer.next().unwrap(), expected);
+        assert_eq!(tokenizer.next().unwrap().index, 4);
+    }
+
+    #[test]
+    fn token_len() {
+        let src = r#"{"k\u00e9y": -12.5e3, "b": [true, null]}"#;
+        let tokens: Vec<_> = Tokenizer::new(src).collect();
+
+        assert_eq!(tokens[1].len(), r#""k\u00e9y""#.len());
+        assert_eq!(&src[tokens[1].index..tokens[1].end()], r#""k\u00e9y""#);
+        assert_eq!(tokens[3].len(), 7);
+        assert_eq!(&src[tokens[3].index..tokens[3].end()], "-12.5e3");
+        assert_eq!(tokens[8].len(), 4);
+        assert_eq!(tokens[11].len(), 1);
+
+        // every span is exactly the token's source text
+        let rebuilt: String = tokens.iter().map(|t| &src[t.index..t.end()]).collect();
+        assert_eq!(rebuilt, src.replace(' ', ""));
+    }
+
+    #[test]
+    fn nbsp_between_tokens() {
+        let mut tokenizer = Tokenizer::new("1\u{a0}2 x\u{3000}");
+        tokenizer.next();
+
+        let expected = Token {
+            line: 1,
+            index: 1,
+            token_type: TokenType::Error {
+                kind: LexErrorKind::InvalidWhitespace,
+                text: "\u{a0}",
+            },
+        };
+        assert_eq!(tokenizer.next().unwrap(), expected);
+        assert_eq!(tokenizer.next().unwrap().index, 3);
+
+        // a stray word stops short of the whitespace after it
+        let keyword = tokenizer.next().unwrap();
+        assert_eq!(keyword.index, 5);
+        assert!(matches!(
+            keyword.token_type,
+            TokenType::Error {
+                kind: LexErrorKind::UnknownKeyword,
+                text: "x"
+            }
+        ));
+        assert_eq!(tokenizer.next().unwrap().index, 6);
+    }
+
+    #[test]
+    fn position_and_resume() {
+        let src = "[1]\n  {}";
+        let mut tokenizer = Tokenizer::new(src);
+        tokenizer.next();
+        tokenizer.next();
+        tokenizer.next();
+        assert_eq!(tokenizer.position(), 3);
+
+        let mut resumed = Tokenizer::new_at(src, tokenizer.position());
+        let token = resumed.next().unwrap();
+        assert_eq!(token.index, 6);
+        assert_eq!(token.line, 2);
+    }
+
+    #[test]
+    fn comments() {
+        let options = TokenizerOptions {
+            comments: CommentStyle::SlashSlash,
+            ..Default::default()
+        };
+        let mut tokenizer = Tokenizer::new("// one\n[ /* two\n */ 1 ]").with_options(options);
+
+        assert_eq!(tokenizer.next().unwrap().line, 2);
+        let token = tokenizer.next().unwrap();
+        assert_eq!(token.token_type, TokenType::Number { text: "1" });
+        assert_eq!(token.line, 3);
+        assert_eq!(
+            tokenizer.next().unwrap().token_type,
+            TokenType::RightSquareBracket
+        );
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        for emit_comments in [false, true] {
+            let options = TokenizerOptions {
+                comments: CommentStyle::SlashSlash,
+                emit_comments,
+                ..Default::default()
+            };
+            let tokens: Vec<_> = Tokenizer::new("[1, /* oops\n2]")
+                .with_options(options)
+                .collect();
+
+            let last = tokens.last().unwrap();
+            assert_eq!(tokens.len(), 4);
+            assert_eq!((last.index, last.line), (4, 1));
+            assert_eq!(
+                last.token_type,
+                TokenType::Error {
+                    kind: LexErrorKind::UnterminatedComment,
+                    text: "/* oops\n2]",
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn emit_comments() {
+        let options = TokenizerOptions {
+            comments: CommentStyle::Both,
+            emit_comments: true,
+            ..Default::default()
+        };
+        let tokenizer = Tokenizer::new("# a\n[1, /* b\n */ 2] // c").with_options(options);
+
+        let tokens: Vec<_> = tokenizer
+            .map(|token| (token.line, token.to_string()))
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (1, "COMMENT\"# a\"".to_string()),
+                (2, "<[>".to_string()),
+                (2, "NUM\"1\"".to_string()),
+                (2, "<,>".to_string()),
+                (2, "COMMENT\"/* b\n */\"".to_string()),
+                (3, "NUM\"2\"".to_string()),
+                (3, "<]>".to_string()),
+                (3, "COMMENT\"// c\"".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn identifiers() {
+        let options = TokenizerOptions {
+            identifiers: true,
+            ..Default::default()
+        };
+        let tokens: Vec<_> = Tokenizer::new("[nullable, true, trueValue,false]")
+            .with_options(options)
+            .map(|token| token.token_type)
+            .collect();
+        assert_eq!(
+            tokens[1],
+            TokenType::Error {
+                kind: LexErrorKind::UnknownKeyword,
+                text: "nullable",
+            }
+        );
+        assert_eq!(tokens[3], TokenType::True);
+        assert_eq!(
+            tokens[5],
+            TokenType::Error {
+                kind: LexErrorKind::UnknownKeyword,
+                text: "trueValue",
+            }
+        );
+        assert_eq!(tokens[7], TokenType::False);
+
+        // without the option a keyword ends wherever its letters do
+        let token = Tokenizer::new("nullable").next().unwrap();
+        assert_eq!(token.token_type, TokenType::Null);
+    }
+
+    #[test]
+    fn single_quoted_string() {
+        let options = TokenizerOptions {
+            single_quotes: true,
+            ..Default::default()
+        };
+        let token = Tokenizer::new(r#"'it\'s "x"'"#)
+            .with_options(options)
+            .next()
+            .unwrap();
+
+        assert_eq!(
+            token.token_type,
+            TokenType::String {
+                text: r#"'it\'s "x"'"#
+            }
+        );
+    }
+
+    #[test]
+    fn malformed_numbers() {
+        for (src, text) in [
+            ("1.2.3", "1.2.3"),
+            ("--1", "--1"),
+            ("1e1e1", "1e1e1"),
+            ("01", "01"),
+            ("1.", "1."),
+            ("1.e5,", "1.e5"),
+            ("2e]", "2e"),
+            ("-1e+", "-1e+"),
+        ] {
+            let actual = Tokenizer::new(src).next().unwrap();
+            assert_eq!(
+                actual.token_type,
+                TokenType::Error {
+                    kind: LexErrorKind::InvalidNumber,
+                    text,
+                },
+                "{}",
+                src
+            );
+        }
+    }
+
+    #[test]
+    fn lone_minus_is_invalid_number() {
+        let actual = Tokenizer::new("-").next().unwrap();
+        assert_eq!(
+            actual.token_type,
+            TokenType::Error {
+                kind: LexErrorKind::InvalidNumber,
+                text: "-",
+            }
+        );
+    }
+
     #[test]
     fn string_with_inner_quote_mark() {
         let expected = Token {
@@ -484,6 +1180,128 @@ mod tests {
         vecs_eq(&actual, &expected);
     }
 
+    #[test]
+    fn indented_token_positions() {
+        let compact = r#"{"a":[1,true,{"b":null}],"c":"d"}"#;
+        let indented = "{\n        \"a\": [\r\n\t\t1,\n                true,\n                {   \"b\":    null   }\n        ],\n\n\n        \"c\":                  \"d\"\n}\n          ";
+
+        let mut expected = Tokenizer::new(compact);
+        let mut actual = Tokenizer::new(indented);
+        loop {
+            let (expected, actual) = match (expected.next(), actual.next()) {
+                (None, None) => break,
+                (Some(e), Some(a)) => (e, a),
+                (e, a) => panic!("token count differs: {:?} {:?}", e, a),
+            };
+
+            assert_eq!(expected.token_type, actual.token_type);
+
+            // Positions must agree with a plain byte-by-byte scan of the source.
+            let before = &indented[..actual.index];
+            assert_eq!(actual.line, before.matches('\n').count() + 1);
+            assert!(!before.is_empty() || actual.index == 0);
+            assert!(!indented.as_bytes()[actual.index].is_ascii_whitespace());
+        }
+    }
+
+    #[test]
+    fn unknown_keyword_stops_at_quote() {
+        let tokens = |source| {
+            Tokenizer::new(source)
+                .map(|token| token.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        vecs_eq(
+            &tokens(r#"[nul,"ok"]"#),
+            &vec![
+                "<[>".to_string(),
+                r#"ERR<unknown keyword>"nul""#.to_string(),
+                "<,>".to_string(),
+                r#"STR""ok"""#.to_string(),
+                "<]>".to_string(),
+            ],
+        );
+        vecs_eq(
+            &tokens(r#"nul"x""#),
+            &vec![
+                r#"ERR<unknown keyword>"nul""#.to_string(),
+                r#"STR""x"""#.to_string(),
+            ],
+        );
+    }
+
+    #[test]
+    fn recover_to_next_comma() {
+        let mut tokenizer = Tokenizer::new("[1, tru e, 3]");
+        for _ in 0..3 {
+            tokenizer.next();
+        }
+        assert!(matches!(
+            tokenizer.next().unwrap().token_type,
+            TokenType::Error { .. }
+        ));
+
+        tokenizer.recover();
+        let comma = tokenizer.next().unwrap();
+        assert_eq!((comma.index, comma.token_type), (9, TokenType::Comma));
+        assert_eq!(tokenizer.next().unwrap().to_string(), r#"NUM"3""#);
+    }
+
+    #[test]
+    fn recover_skips_nested_values() {
+        let source = "{\"a\": x [1, {\"b\": 2}] \"c,d\",\n \"e\": 3}";
+        let mut tokenizer = Tokenizer::new(source);
+        for _ in 0..4 {
+            tokenizer.next();
+        }
+
+        tokenizer.recover();
+        let comma = tokenizer.next().unwrap();
+        assert_eq!(comma.token_type, TokenType::Comma);
+        assert_eq!(comma.index, source.find(",\n").unwrap());
+        assert_eq!(tokenizer.next().unwrap().line, 2);
+
+        let mut tokenizer = Tokenizer::new("[x y]");
+        tokenizer.next();
+        tokenizer.recover();
+        assert_eq!(
+            tokenizer.next().unwrap().token_type,
+            TokenType::RightSquareBracket
+        );
+        tokenizer.recover();
+        assert_eq!(tokenizer.next(), None);
+    }
+
+    #[test]
+    fn peek_token() {
+        let mut tokenizer = Tokenizer::new("[1, \"a\"]");
+        tokenizer.next();
+
+        let peeked = tokenizer.peek_token().cloned();
+        assert_eq!(peeked.as_ref().unwrap().to_string(), r#"NUM"1""#);
+        assert_eq!(tokenizer.peek_token().cloned(), peeked);
+        assert_eq!(tokenizer.position(), 1);
+        assert_eq!(tokenizer.next(), peeked);
+        assert_eq!(tokenizer.position(), 2);
+
+        assert_eq!(tokenizer.next().unwrap().token_type, TokenType::Comma);
+        assert_eq!(tokenizer.peek_token().unwrap().index, 4);
+        tokenizer.next();
+        tokenizer.next();
+        assert_eq!(tokenizer.peek_token(), None);
+        assert_eq!(tokenizer.next(), None);
+    }
+
+    #[test]
+    fn lanes_equal_matches_each_byte() {
+        let word = u64::from_le_bytes(*b" \n\t\r x \n");
+        assert_eq!(lanes_equal(word, b' ').count_ones(), 3);
+        assert_eq!(lanes_equal(word, b'\n').count_ones(), 2);
+        assert_eq!(lanes_equal(word, b'x').count_ones(), 1);
+        assert_eq!(lanes_equal(word, b'\0'), 0);
+    }
+
     fn vecs_eq<T: PartialEq + std::fmt::Debug>(a: &Vec<T>, b: &Vec<T>) {
         assert_eq!(a.len(), b.len());
 
@@ -0,0 +1,415 @@
+use crate::{
+    error::ParseError,
+    escape::escape_string,
+    parser::{parse, CommentStyle, JsonNode},
+    token::{LexErrorKind, Token, TokenType},
+    tokenizer::{Tokenizer, TokenizerOptions},
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// A value together with the comments around it, for formatters that must
+/// not lose them. Comments are kept as their source text, delimiters included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonNodeWithTrivia {
+    /// Comments on the lines before the value (or before its key).
+    pub leading: Vec<String>,
+    pub value: TriviaValue,
+    /// Comments after the value, and its comma, on the same line.
+    pub trailing: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriviaValue {
+    /// A string, number, boolean or null, with its exact source text.
+    Scalar { node: JsonNode, text: String },
+    /// `dangling` holds the comments after the last element.
+    Array {
+        items: Vec<JsonNodeWithTrivia>,
+        dangling: Vec<String>,
+    },
+    /// Members in source order.
+    Object {
+        members: Vec<(String, JsonNodeWithTrivia)>,
+        dangling: Vec<String>,
+    },
+}
+
+/// Parses a document with `//`, `/* */` and `#` comments, attaching each
+/// comment to the value it belongs to. The document is otherwise strict JSON.
+pub fn parse_with_trivia(source: &str) -> Result<JsonNodeWithTrivia, ParseError> {
+    let options = TokenizerOptions {
+        comments: CommentStyle::Both,
+        emit_comments: true,
+        ..Default::default()
+    };
+    let mut parser = TriviaParser {
+        source,
+        tokenizer: Tokenizer::new(source).with_options(options),
+        last_line: 1,
+    };
+
+    let mut root = parser.value()?;
+    root.trailing.extend(parser.comments());
+    match parser.advance() {
+        Some(token) => Err(parser.error("unexpected text after value", &token)),
+        None => Ok(root),
+    }
+}
+
+impl JsonNodeWithTrivia {
+    /// Drops the comments.
+    pub fn to_node(&self) -> JsonNode {
+        match &self.value {
+            TriviaValue::Scalar { node, .. } => node.clone(),
+            TriviaValue::Array { items, .. } => items.iter().map(|item| item.to_node()).collect(),
+            TriviaValue::Object { members, .. } => members
+                .iter()
+                .map(|(key, value)| (key.clone(), value.to_node()))
+                .collect(),
+        }
+    }
+
+    /// Writes the document indented by `indent` spaces per level, with every
+    /// comment back in its place: leading comments on their own lines above
+    /// the value and trailing ones after it.
+    pub fn to_string_pretty(&self, indent: usize) -> String {
+        let mut out = String::new();
+        for comment in &self.leading {
+            out.push_str(comment);
+            out.push('\n');
+        }
+        self.write_value(&mut out, indent, 0);
+        for comment in &self.trailing {
+            out.push(' ');
+            out.push_str(comment);
+        }
+        out
+    }
+
+    fn write_value(&self, out: &mut String, indent: usize, depth: usize) {
+        let (open, close, dangling) = match &self.value {
+            TriviaValue::Scalar { text, .. } => {
+                out.push_str(text);
+                return;
+            }
+            TriviaValue::Array { dangling, .. } => ('[', ']', dangling),
+            TriviaValue::Object { dangling, .. } => ('{', '}', dangling),
+        };
+
+        let children: Vec<(Option<&String>, &JsonNodeWithTrivia)> = match &self.value {
+            TriviaValue::Array { items, .. } => items.iter().map(|item| (None, item)).collect(),
+            TriviaValue::Object { members, .. } => members
+                .iter()
+                .map(|(key, value)| (Some(key), value))
+                .collect(),
+            TriviaValue::Scalar { .. } => unreachable!(),
+        };
+
+        out.push(open);
+        for (i, (key, child)) in children.iter().enumerate() {
+            for comment in &child.leading {
+                newline(out, indent, depth + 1);
+                out.push_str(comment);
+            }
+            newline(out, indent, depth + 1);
+            if let Some(key) = key {
+                out.push_str(&escape_string(key));
+                out.push_str(": ");
+            }
+            child.write_value(out, indent, depth + 1);
+            if i + 1 < children.len() {
+                out.push(',');
+            }
+            for comment in &child.trailing {
+                out.push(' ');
+                out.push_str(comment);
+            }
+        }
+        for comment in dangling {
+            newline(out, indent, depth + 1);
+            out.push_str(comment);
+        }
+        if !children.is_empty() || !dangling.is_empty() {
+            newline(out, indent, depth);
+        }
+        out.push(close);
+    }
+}
+
+fn newline(out: &mut String, indent: usize, depth: usize) {
+    out.push('\n');
+    out.push_str(&" ".repeat(indent * depth));
+}
+
+struct TriviaParser<'a> {
+    source: &'a str,
+    tokenizer: Tokenizer<'a>,
+    /// The line of the last non-comment token, to tell trailing comments
+    /// from leading ones.
+    last_line: usize,
+}
+
+impl<'a> TriviaParser<'a> {
+    fn value(&mut self) -> Result<JsonNodeWithTrivia, ParseError> {
+        let leading = self.comments();
+        let token = self.expect("eof")?;
+
+        let value = match token.token_type {
+            TokenType::LeftSquareBracket => self.array()?,
+            TokenType::LeftCurlyBracket => self.object()?,
+            TokenType::String { .. }
+            | TokenType::Number { .. }
+            | TokenType::True
+            | TokenType::False
+            | TokenType::Null => {
+                let text = &self.source[token.index..self.tokenizer.position()];
+                let node = parse(text).map_err(|e| self.error(&e.message, &token))?;
+                TriviaValue::Scalar {
+                    node,
+                    text: text.to_string(),
+                }
+            }
+            TokenType::Error { kind, .. } => return Err(self.error(kind.message(), &token)),
+            _ => return Err(self.error("expected a value", &token)),
+        };
+
+        Ok(JsonNodeWithTrivia {
+            leading,
+            value,
+            trailing: vec![],
+        })
+    }
+
+    fn array(&mut self) -> Result<TriviaValue, ParseError> {
+        let mut items = vec![];
+        loop {
+            let leading = self.comments();
+            if let Some(token) = self.peek() {
+                if token.token_type == TokenType::RightSquareBracket {
+                    self.advance();
+                    return Ok(TriviaValue::Array {
+                        items,
+                        dangling: leading,
+                    });
+                }
+            }
+
+            let mut item = self.value()?;
+            item.leading.splice(0..0, leading);
+            let dangling = self.separator(&mut item, TokenType::RightSquareBracket)?;
+            items.push(item);
+            if let Some(dangling) = dangling {
+                return Ok(TriviaValue::Array { items, dangling });
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<TriviaValue, ParseError> {
+        let mut members = vec![];
+        loop {
+            let mut leading = self.comments();
+            let token = self.expect("eof")?;
+            let key = match token.token_type {
+                TokenType::RightCurlyBracket => {
+                    return Ok(TriviaValue::Object {
+                        members,
+                        dangling: leading,
+                    })
+                }
+                TokenType::String { text } => match parse(text) {
+                    Ok(JsonNode::String(key)) => key,
+                    _ => return Err(self.error("invalid object key", &token)),
+                },
+                _ => {
+                    let message = format!("object key must be a string, found {}", token);
+                    return Err(self.error(&message, &token));
+                }
+            };
+
+            // comments between the key and its colon go above the member
+            leading.extend(self.comments());
+            let colon = self.expect("eof")?;
+            if colon.token_type != TokenType::Colon {
+                return Err(self.error("expected colon", &colon));
+            }
+
+            let mut value = self.value()?;
+            value.leading.splice(0..0, leading);
+            let dangling = self.separator(&mut value, TokenType::RightCurlyBracket)?;
+            members.push((key, value));
+            if let Some(dangling) = dangling {
+                return Ok(TriviaValue::Object { members, dangling });
+            }
+        }
+    }
+
+    /// Consumes the comma or closing bracket after a child, collecting the
+    /// same-line comments around it as the child's trailing trivia. At the
+    /// closing bracket, returns the comments on the lines before it as the
+    /// container's dangling trivia; `None` means another child follows.
+    fn separator(
+        &mut self,
+        child: &mut JsonNodeWithTrivia,
+        close: TokenType<'a>,
+    ) -> Result<Option<Vec<String>>, ParseError> {
+        child.trailing = self.trailing_comments();
+        let rest = self.comments();
+        let token = self.expect("unexpected eof")?;
+        if token.token_type == TokenType::Comma {
+            child.trailing.extend(rest);
+            child.trailing.extend(self.trailing_comments());
+            Ok(None)
+        } else if token.token_type == close {
+            Ok(Some(rest))
+        } else {
+            Err(self.error("expected comma or close", &token))
+        }
+    }
+
+    fn comments(&mut self) -> Vec<String> {
+        let mut comments = vec![];
+        while let Some(text) = self.peek().and_then(comment_text) {
+            comments.push(text.to_string());
+            self.tokenizer.next();
+        }
+        comments
+    }
+
+    fn trailing_comments(&mut self) -> Vec<String> {
+        let mut comments = vec![];
+        loop {
+            let line = self.last_line;
+            match self.peek() {
+                Some(token) if token.line == line => match comment_text(token) {
+                    Some(text) => comments.push(text.to_string()),
+                    None => break,
+                },
+                _ => break,
+            }
+            self.tokenizer.next();
+        }
+        comments
+    }
+
+    fn expect(&mut self, eof: &str) -> Result<Token<'a>, ParseError> {
+        self.advance().ok_or_else(|| ParseError::eof(eof))
+    }
+
+    fn advance(&mut self) -> Option<Token<'a>> {
+        let token = self.tokenizer.next()?;
+        self.last_line = token.line;
+        Some(token)
+    }
+
+    fn peek(&mut self) -> Option<&Token<'a>> {
+        self.tokenizer.peek_token()
+    }
+
+    fn error(&self, message: &str, token: &Token) -> ParseError {
+        // a comment can be cut off wherever whitespace may go, and is the
+        // problem whatever was expected there
+        let message = match token.token_type {
+            TokenType::Error {
+                kind: kind @ LexErrorKind::UnterminatedComment,
+                ..
+            } => kind.message(),
+            _ => message,
+        };
+        ParseError::at(message, self.source, token.index, token.line)
+    }
+}
+
+fn comment_text<'a>(token: &Token<'a>) -> Option<&'a str> {
+    match token.token_type {
+        TokenType::LineComment { text } | TokenType::BlockComment { text } => Some(text),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unterminated_comment() {
+        for source in ["{\"a\":1 /* oops", "[1] /* oops", "/* oops\n[]"] {
+            let err = parse_with_trivia(source).unwrap_err();
+            assert_eq!(err.message, "unterminated comment", "{}", source);
+            assert_eq!(err.index, source.find("/*"));
+        }
+    }
+
+    #[test]
+    fn round_trip_comments() {
+        let source = r#"// settings
+{
+  # where to listen
+  "port": 8080, // default
+  "hosts": [
+    "a", /* primary */
+    // fallback
+    "b"
+  ],
+  "empty": {
+    // nothing yet
+  }
+} // end"#;
+
+        let doc = parse_with_trivia(source).unwrap();
+        assert_eq!(doc.to_string_pretty(2), source);
+        assert_eq!(
+            doc.to_node(),
+            parse(r#"{"port":8080,"hosts":["a","b"],"empty":{}}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn reformat_keeps_comments_in_place() {
+        let source = "{\"a\":1,// one\n\"b\" : [ /* none */ ] }";
+
+        let doc = parse_with_trivia(source).unwrap();
+        assert_eq!(
+            doc.to_string_pretty(2),
+            "{\n  \"a\": 1, // one\n  \"b\": [\n    /* none */\n  ]\n}"
+        );
+    }
+
+    #[test]
+    fn comment_before_close() {
+        let source = "[\n  1\n  // last\n]";
+        let doc = parse_with_trivia(source).unwrap();
+        match &doc.value {
+            TriviaValue::Array { dangling, .. } => assert_eq!(dangling, &["// last"]),
+            _ => panic!("expected an array"),
+        }
+        assert_eq!(doc.to_string_pretty(2), source);
+
+        let doc = parse_with_trivia("{\"a\": 1 /* same line */\n# next\n}").unwrap();
+        assert_eq!(
+            doc.to_string_pretty(2),
+            "{\n  \"a\": 1 /* same line */\n  # next\n}"
+        );
+    }
+
+    #[test]
+    fn comment_between_key_and_colon() {
+        let doc = parse_with_trivia("{\"a\" /* c */ : 1}").unwrap();
+        assert_eq!(doc.to_node(), parse(r#"{"a":1}"#).unwrap());
+        assert_eq!(doc.to_string_pretty(2), "{\n  /* c */\n  \"a\": 1\n}");
+    }
+
+    #[test]
+    fn trivia_errors() {
+        let err = parse_with_trivia("[1 // c\n 2]").unwrap_err();
+        assert_eq!(err.message, "expected comma or close");
+        assert_eq!(err.index, Some(9));
+
+        assert!(parse_with_trivia("[1] 2").is_err());
+        assert!(parse_with_trivia("// only a comment").is_err());
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    escape::escape_string,
+    number::Number,
+    parser::JsonNode,
+    ser::{write_number, JsonWriter, SerializeOptions},
+};
+use alloc::{string::String, vec::Vec};
+use core::convert::Infallible;
+
+/// Writes `node` as block-style YAML: one map entry or sequence item per
+/// line, indented two spaces per level. Strings that would read as another
+/// type, or need escaping, are double-quoted. Keys are written in key order.
+pub fn to_yaml_string(node: &JsonNode) -> String {
+    let mut writer = YamlWriter::default();
+    match node.write_to(&mut writer) {
+        Ok(()) => {}
+        Err(never) => match never {},
+    }
+    writer.out.push('\n');
+    writer.out
+}
+
+/// An open sequence or map.
+struct Block {
+    is_map: bool,
+    /// The column its entries start at.
+    indent: usize,
+    /// The first entry continues the current line, after a `- ` or at the
+    /// start of the document.
+    inline: bool,
+    entries: usize,
+}
+
+/// Where the value about to be written goes.
+#[derive(PartialEq)]
+enum Slot {
+    Document,
+    Item,
+    MapValue,
+}
+
+#[derive(Default)]
+struct YamlWriter {
+    out: String,
+    blocks: Vec<Block>,
+    /// A key was just written, so the next value is its value.
+    after_key: bool,
+}
+
+impl YamlWriter {
+    /// Starts a new line for the next entry of the innermost block, unless
+    /// it is the first entry of a block that continues the current line.
+    fn entry(&mut self) {
+        if let Some(block) = self.blocks.last_mut() {
+            if block.entries > 0 || !block.inline {
+                self.out.push('\n');
+                self.out.push_str(&" ".repeat(block.indent));
+            }
+            block.entries += 1;
+        }
+    }
+
+    /// Writes whatever goes before a value: the `- ` of a sequence item, or
+    /// nothing at the top level and after a key.
+    fn slot(&mut self) -> Slot {
+        if core::mem::take(&mut self.after_key) {
+            return Slot::MapValue;
+        }
+        match self.blocks.last() {
+            Some(block) if !block.is_map => {
+                self.entry();
+                self.out.push_str("- ");
+                Slot::Item
+            }
+            _ => Slot::Document,
+        }
+    }
+
+    fn scalar(&mut self, text: &str) -> Result<(), Infallible> {
+        if self.slot() == Slot::MapValue {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+        Ok(())
+    }
+
+    fn begin(&mut self, is_map: bool, len: usize) -> Result<(), Infallible> {
+        let slot = self.slot();
+        let parent = self.blocks.last().map_or(0, |block| block.indent);
+        // an empty block is written in flow style and gets no entries
+        if len == 0 {
+            if slot == Slot::MapValue {
+                self.out.push(' ');
+            }
+            self.out.push_str(if is_map { "{}" } else { "[]" });
+        }
+
+        let (indent, inline) = match slot {
+            Slot::Document => (0, true),
+            Slot::Item => (parent + 2, true),
+            Slot::MapValue => (parent + 2, false),
+        };
+        self.blocks.push(Block {
+            is_map,
+            indent,
+            inline,
+            entries: 0,
+        });
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<(), Infallible> {
+        self.blocks.pop();
+        Ok(())
+    }
+}
+
+impl JsonWriter for YamlWriter {
+    type Error = Infallible;
+
+    fn null(&mut self) -> Result<(), Infallible> {
+        self.scalar("null")
+    }
+
+    fn bool(&mut self, b: bool) -> Result<(), Infallible> {
+        self.scalar(if b { "true" } else { "false" })
+    }
+
+    fn number(&mut self, n: Number) -> Result<(), Infallible> {
+        let f = n.as_f64();
+        if f.is_nan() {
+            return self.scalar(".nan");
+        }
+        if f.is_infinite() {
+            return self.scalar(if f > 0.0 { ".inf" } else { "-.inf" });
+        }
+        let mut text = String::new();
+        // finite numbers always serialize
+        let _ = write_number(n, &mut text, &SerializeOptions::default());
+        self.scalar(&text)
+    }
+
+    fn raw_number(&mut self, text: &str) -> Result<(), Infallible> {
+        self.scalar(text)
+    }
+
+    fn string(&mut self, s: &str) -> Result<(), Infallible> {
+        self.scalar(&quote(s))
+    }
+
+    fn begin_array(&mut self, len: usize) -> Result<(), Infallible> {
+        self.begin(false, len)
+    }
+
+    fn end_array(&mut self) -> Result<(), Infallible> {
+        self.end()
+    }
+
+    fn begin_object(&mut self, len: usize) -> Result<(), Infallible> {
+        self.begin(true, len)
+    }
+
+    fn key(&mut self, key: &str) -> Result<(), Infallible> {
+        self.entry();
+        self.out.push_str(&quote(key));
+        self.out.push(':');
+        self.after_key = true;
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> Result<(), Infallible> {
+        self.end()
+    }
+
+    fn sort_keys(&self) -> bool {
+        true
+    }
+}
+
+/// `s` as a plain scalar when that reads back as the same string, and as a
+/// double-quoted one otherwise. JSON string escapes are valid YAML escapes.
+fn quote(s: &str) -> String {
+    let plain = s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && !s.ends_with(' ')
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ' '))
+        && !matches!(
+            s.to_ascii_lowercase().as_str(),
+            "true" | "false" | "null" | "yes" | "no" | "on" | "off" | "y" | "n"
+        )
+        // YAML parsers read `nan`, `inf` and `infinity` as floats
+        && s.parse::<f64>().is_err();
+    if plain {
+        String::from(s)
+    } else {
+        escape_string(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::GITHUB_EVENT, parser::parse};
+    use yaml_rust2::{Yaml, YamlLoader};
+
+    /// Reads `yaml` back with a real YAML parser.
+    fn load(yaml: &str) -> JsonNode {
+        let docs = YamlLoader::load_from_str(yaml).unwrap();
+        assert_eq!(docs.len(), 1, "{}", yaml);
+        from_yaml(&docs[0])
+    }
+
+    fn from_yaml(yaml: &Yaml) -> JsonNode {
+        match yaml {
+            Yaml::Null => JsonNode::Null,
+            Yaml::Boolean(b) => JsonNode::from(*b),
+            Yaml::Integer(n) => JsonNode::from(*n),
+            Yaml::Real(_) => JsonNode::from(yaml.as_f64().unwrap()),
+            Yaml::String(s) => JsonNode::from(s.as_str()),
+            Yaml::Array(items) => JsonNode::Array(items.iter().map(from_yaml).collect()),
+            Yaml::Hash(map) => map
+                .iter()
+                .map(|(key, value)| (key.as_str().unwrap().to_string(), from_yaml(value)))
+                .collect(),
+            other => panic!("unexpected YAML value {:?}", other),
+        }
+    }
+
+    #[test]
+    fn small_object() {
+        let json = parse(
+            r#"{"name": "json_parser", "version": 1.5, "tags": ["json", "yes", ""],
+                "deps": {}, "owner": {"id": 7, "admin": false, "note": null},
+                "matrix": [[1, 2], [], {"a b": "x: y", "c": [true]}]}"#,
+        )
+        .unwrap();
+
+        let expected = "\
+deps: {}
+matrix:
+  - - 1
+    - 2
+  - []
+  - a b: \"x: y\"
+    c:
+      - true
+name: json_parser
+owner:
+  admin: false
+  id: 7
+  note: null
+tags:
+  - json
+  - \"yes\"
+  - \"\"
+version: 1.5
+";
+        assert_eq!(to_yaml_string(&json), expected);
+        assert_eq!(load(expected), json);
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(to_yaml_string(&parse("12").unwrap()), "12\n");
+        assert_eq!(to_yaml_string(&parse("[]").unwrap()), "[]\n");
+        assert_eq!(to_yaml_string(&JsonNode::from(f64::NAN)), ".nan\n");
+        assert_eq!(to_yaml_string(&JsonNode::from(-f64::INFINITY)), "-.inf\n");
+        assert_eq!(to_yaml_string(&parse(r#""a\nb""#).unwrap()), "\"a\\nb\"\n");
+        assert_eq!(to_yaml_string(&parse(r#""-1""#).unwrap()), "\"-1\"\n");
+        assert_eq!(to_yaml_string(&parse(r#""Null""#).unwrap()), "\"Null\"\n");
+
+        for text in [
+            "nan",
+            "Infinity",
+            "inf",
+            "e",
+            "1_000",
+            "a/b.c",
+            "x - y",
+            "\u{e9}t\u{e9}",
+        ] {
+            let json = JsonNode::from(text);
+            assert_eq!(load(&to_yaml_string(&json)), json, "{}", text);
+        }
+    }
+
+    #[test]
+    fn fixture() {
+        let yaml = to_yaml_string(&parse(GITHUB_EVENT).unwrap());
+        assert!(yaml.starts_with("actor:\n  avatar_url: \"https://avatars"));
+        assert!(yaml.contains("\n  gravatar_id: \"\"\n"));
+        assert!(yaml.contains("\nid: \"2489651045\"\n"));
+        assert!(yaml.contains("\npublic: true\n"));
+        assert_eq!(load(&yaml), parse(GITHUB_EVENT).unwrap());
+    }
+}
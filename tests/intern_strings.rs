@@ -0,0 +1,32 @@
+//! Measures what the parser allocates through a counting global allocator.
+//! This binary holds a single test, so no other test allocates meanwhile.
+#![cfg(feature = "std")]
+
+use json_parser::{parser::ParseOptions, rc::parse_rc};
+use stats_alloc::{Region, StatsAlloc, INSTRUMENTED_SYSTEM};
+use std::alloc::System;
+
+#[global_allocator]
+static GLOBAL: &StatsAlloc<System> = &INSTRUMENTED_SYSTEM;
+
+#[test]
+fn interned_strings_allocate_less() {
+    let value = "x".repeat(1000);
+    let doc = format!("[{}]", vec![format!("{:?}", value); 1000].join(","));
+    let retained = |options: &ParseOptions| {
+        let reg = Region::new(GLOBAL);
+        let json = parse_rc(&doc, options).unwrap();
+        let stats = reg.change();
+        drop(json);
+        stats.bytes_allocated as isize - stats.bytes_deallocated as isize
+    };
+
+    let plain = retained(&ParseOptions::default());
+    let interned = retained(&ParseOptions {
+        intern_strings: true,
+        ..Default::default()
+    });
+    // a thousand 1000-byte strings against one and a thousand pointers to it
+    assert!(plain > 1_000_000, "{}", plain);
+    assert!(interned < 100_000, "{}", interned);
+}